@@ -0,0 +1,189 @@
+//! Provides a baseline heuristic for bots to rate boards with, and a greedy bot built on it.
+//!
+//! [`best_placement()`] picks the placement that leaves the best board. Apply it to the game with
+//! [`Game::hold_piece()`] if needed, followed by [`Game::apply_placement()`].
+
+use crate::game::{Game, HoldOutcome, Placement, Playfield};
+
+/// The weights of the features that [`evaluate_with()`] sums up.
+///
+/// Features that make a board worse should have a negative weight.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvalWeights {
+    /// The weight of the sum of all column heights.
+    pub aggregate_height: f64,
+    /// The weight of the amount of empty tiles covered by set tiles.
+    pub holes: f64,
+    /// The weight of the sum of the height differences between neighboring columns.
+    pub bumpiness: f64,
+    /// The weight of the amount of completed lines.
+    pub lines: f64
+}
+
+impl EvalWeights {
+    /// Gets a well-known set of weights tuned for the guideline playfield.
+    pub const fn standard() -> EvalWeights {
+        EvalWeights {
+            aggregate_height: -0.510066,
+            holes: -0.35663,
+            bumpiness: -0.184483,
+            lines: 0.760666
+        }
+    }
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        EvalWeights::standard()
+    }
+}
+
+/// A move chosen by [`best_placement()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BotMove {
+    /// Whether to hold the active piece first, and place the piece that becomes active instead.
+    pub hold: bool,
+    /// Where to place the piece.
+    pub placement: Placement
+}
+
+/// Rates a board with the [standard weights](EvalWeights::standard). Higher is better.
+pub fn evaluate(board: &Playfield) -> f64 {
+    evaluate_with(board, &EvalWeights::standard())
+}
+
+/// Rates a board as the weighted sum of its features. Higher is better.
+///
+/// Completed lines still on the board are counted, then measured as if they were already cleared.
+pub fn evaluate_with(board: &Playfield, weights: &EvalWeights) -> f64 {
+    let completed = board.find_completed_lines();
+
+    let cleared;
+    let board = if completed.is_empty() {
+        board
+    } else {
+        let mut copy = board.clone();
+        copy.remove_lines(&completed);
+        cleared = copy;
+        &cleared
+    };
+
+    weights.aggregate_height * board.aggregate_height() as f64
+        + weights.holes * board.hole_count() as f64
+        + weights.bumpiness * board.bumpiness() as f64
+        + weights.lines * completed.len() as f64
+}
+
+/// Finds the placement of the active piece, or of the piece that holding would make active,
+/// that leaves the highest rated board. This does not change the game.
+///
+/// Returns [`None`] if the game is over, during the entry delay, or if no placement exists.
+/// On ties, placements of the active piece are preferred over holding.
+pub fn best_placement(game: &Game, weights: &EvalWeights) -> Option<BotMove> {
+    if game.is_game_over() || game.is_in_are() {
+        return None;
+    }
+
+    let mut best = None;
+    rate_placements(game, false, weights, &mut best);
+
+    if game.can_hold() {
+        let mut held = game.clone();
+        if held.hold_piece() != HoldOutcome::Blocked && !held.is_game_over() {
+            rate_placements(&held, true, weights, &mut best);
+        }
+    }
+
+    best.map(|(_, bot_move)| bot_move)
+}
+
+/// Rates every placement of the active piece and keeps the best one seen so far.
+fn rate_placements(game: &Game, hold: bool, weights: &EvalWeights, best: &mut Option<(f64, BotMove)>) {
+    for placement in game.enumerate_placements() {
+        let mut piece = game.active_piece().clone();
+        piece.rotation = placement.rotation;
+        piece.position = placement.position;
+
+        let mut board = game.playfield().clone();
+        board.copy_in_piece(&piece);
+
+        let score = evaluate_with(&board, weights);
+        let is_better = match best {
+            Some((best_score, _)) => score > *best_score,
+            None => true
+        };
+
+        if is_better {
+            *best = Some((score, BotMove { hold, placement }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pieces::PieceKind;
+    use crate::randomizer::{FixedSequence, SequenceEnd};
+
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    #[test]
+    fn holes_score_worse() {
+        let solid = Playfield::from_ascii("
+            ####......
+            ####......
+        ").unwrap();
+        let holed = Playfield::from_ascii("
+            ####......
+            ##.#......
+        ").unwrap();
+
+        assert_eq!(holed.hole_count(), 1);
+        assert!(evaluate(&holed) < evaluate(&solid));
+    }
+
+    #[test]
+    fn completed_lines_are_rated_as_cleared() {
+        let full = Playfield::from_ascii("##########").unwrap();
+        let weights = EvalWeights::standard();
+        assert_eq!(evaluate_with(&full, &weights), weights.lines);
+    }
+
+    /// Lets the bot place the active piece, holding first if it chose to.
+    fn play_bot_move(game: &mut Game) {
+        let bot_move = best_placement(game, &EvalWeights::standard()).expect("no placement found");
+        if bot_move.hold {
+            game.hold_piece();
+        }
+
+        game.apply_placement(bot_move.placement).unwrap();
+    }
+
+    #[test]
+    fn bot_places_flush_on_flat_board() {
+        // S and Z pieces cannot avoid a hole on a flat board
+        for kind in [PieceKind::I, PieceKind::J, PieceKind::L, PieceKind::O, PieceKind::T] {
+            let randomizer = FixedSequence::new(vec![kind], SequenceEnd::RepeatLast);
+            let mut game = Game::with_randomizer(Box::new(randomizer));
+            play_bot_move(&mut game);
+
+            let board = game.playfield();
+            assert_eq!(board.hole_count(), 0, "{:?} left a hole", kind);
+            assert!(board.max_height() <= 2, "{:?} was not placed flat", kind);
+        }
+    }
+
+    #[test]
+    fn bot_fits_s_into_step() {
+        let mut game = Game::with_seed(0);
+        *game.playfield_mut() = Playfield::from_ascii("..#.......").unwrap();
+        game.force_active_piece(PieceKind::S);
+        game.set_next_queue(&[PieceKind::S]);
+
+        play_bot_move(&mut game);
+        assert_eq!(game.playfield().hole_count(), 0);
+        assert_eq!(game.playfield().max_height(), 2);
+    }
+}
@@ -0,0 +1,316 @@
+//! Provides a builder to configure a game in one go.
+
+use crate::game::{Game, LockMode, PlayfieldConfig};
+use crate::palette::Palette;
+use crate::randomizer::{Randomizer, SevenBag};
+
+use alloc::boxed::Box;
+
+/// Configures a [`Game`] with chained calls, ending with [`GameBuilder::build()`].
+///
+/// ```
+/// use tetromino_core::GameBuilder;
+///
+/// let game = GameBuilder::new()
+///     .seed(42)
+///     .size(12, 24)
+///     .preview(5)
+///     .lock_delay(0.5)
+///     .build();
+///
+/// assert_eq!(game.preview_count(), 5);
+/// assert_eq!(game.playfield().width(), 12);
+/// ```
+///
+/// Settings that are not given keep the defaults of [`Game`].
+#[derive(Clone, Default)]
+pub struct GameBuilder {
+    config: Option<PlayfieldConfig>,
+    randomizer: Option<Box<dyn Randomizer>>,
+    preview_count: Option<usize>,
+    hold_capacity: Option<usize>,
+    lock_delay: Option<f64>,
+    lock_mode: Option<LockMode>,
+    gravity: Option<f64>,
+    are: Option<f64>,
+    input_buffering: Option<bool>,
+    das: Option<f64>,
+    arr: Option<f64>,
+    scale_factor: Option<usize>,
+    spawn_row_nudge: Option<bool>,
+    tracks_cell_ages: Option<bool>,
+    danger_threshold: Option<usize>,
+    palette: Option<Palette>,
+}
+
+impl GameBuilder {
+    /// Creates a builder with all settings at their defaults.
+    pub fn new() -> Self {
+        GameBuilder::default()
+    }
+
+    /// Draws pieces from a [`SevenBag`] with the given seed, like [`Game::with_seed()`].
+    ///
+    /// Without a seed or randomizer, the bag is seeded from entropy. That needs the `std` feature;
+    /// without it, a seed of 0 is used instead.
+    pub fn seed(self, seed: u64) -> Self {
+        self.randomizer(Box::new(SevenBag::with_seed(seed)))
+    }
+
+    /// Draws pieces from the given randomizer, replacing any seed given before.
+    pub fn randomizer(mut self, randomizer: Box<dyn Randomizer>) -> Self {
+        self.randomizer = Some(randomizer);
+        self
+    }
+
+    /// Sets the dimensions of the playfield in visible blocks.
+    pub fn size(self, width: usize, height: usize) -> Self {
+        self.config(PlayfieldConfig { width, height })
+    }
+
+    /// Sets the dimensions of the playfield.
+    pub fn config(mut self, config: PlayfieldConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// See [`Game::set_preview_count()`].
+    pub fn preview(mut self, count: usize) -> Self {
+        self.preview_count = Some(count);
+        self
+    }
+
+    /// See [`Game::set_hold_capacity()`].
+    pub fn hold_capacity(mut self, capacity: usize) -> Self {
+        self.hold_capacity = Some(capacity);
+        self
+    }
+
+    /// See [`Game::set_lock_delay()`].
+    pub fn lock_delay(mut self, seconds: f64) -> Self {
+        self.lock_delay = Some(seconds);
+        self
+    }
+
+    /// See [`Game::set_lock_mode()`].
+    pub fn lock_mode(mut self, mode: LockMode) -> Self {
+        self.lock_mode = Some(mode);
+        self
+    }
+
+    /// See [`Game::set_gravity()`].
+    pub fn gravity(mut self, cells_per_second: f64) -> Self {
+        self.gravity = Some(cells_per_second);
+        self
+    }
+
+    /// See [`Game::set_are()`].
+    pub fn are(mut self, seconds: f64) -> Self {
+        self.are = Some(seconds);
+        self
+    }
+
+    /// See [`Game::set_input_buffering()`].
+    pub fn input_buffering(mut self, buffer: bool) -> Self {
+        self.input_buffering = Some(buffer);
+        self
+    }
+
+    /// See [`Game::set_das()`].
+    pub fn das(mut self, delay: f64) -> Self {
+        self.das = Some(delay);
+        self
+    }
+
+    /// See [`Game::set_arr()`].
+    pub fn arr(mut self, rate: f64) -> Self {
+        self.arr = Some(rate);
+        self
+    }
+
+    /// See [`Game::restart_with_scale_factor()`]. The [size](GameBuilder::size) is the displayed size.
+    pub fn scale_factor(mut self, factor: usize) -> Self {
+        self.scale_factor = Some(factor);
+        self
+    }
+
+    /// See [`Game::set_spawn_row_nudge()`].
+    pub fn spawn_row_nudge(mut self, enabled: bool) -> Self {
+        self.spawn_row_nudge = Some(enabled);
+        self
+    }
+
+    /// See [`Game::set_tracks_cell_ages()`].
+    pub fn tracks_cell_ages(mut self, enabled: bool) -> Self {
+        self.tracks_cell_ages = Some(enabled);
+        self
+    }
+
+    /// See [`Game::set_danger_threshold()`].
+    pub fn danger_threshold(mut self, rows: usize) -> Self {
+        self.danger_threshold = Some(rows);
+        self
+    }
+
+    /// See [`Game::set_palette()`].
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Creates the game with all settings applied.
+    ///
+    /// Panics if a setting is invalid, in the same cases its setter on [`Game`] panics.
+    pub fn build(self) -> Game {
+        let config = self.config.unwrap_or_default();
+        let randomizer = self.randomizer.unwrap_or_else(default_randomizer);
+        let mut game = Game::with_config_and_randomizer(config, randomizer);
+
+        // This restarts the game, so it goes first
+        if let Some(factor) = self.scale_factor {
+            game.restart_with_scale_factor(factor);
+        }
+
+        if let Some(count) = self.preview_count {
+            game.set_preview_count(count);
+        }
+
+        if let Some(capacity) = self.hold_capacity {
+            game.set_hold_capacity(capacity);
+        }
+
+        if let Some(seconds) = self.lock_delay {
+            game.set_lock_delay(seconds);
+        }
+
+        if let Some(mode) = self.lock_mode {
+            game.set_lock_mode(mode);
+        }
+
+        if let Some(cells_per_second) = self.gravity {
+            game.set_gravity(cells_per_second);
+        }
+
+        if let Some(seconds) = self.are {
+            game.set_are(seconds);
+        }
+
+        if let Some(buffer) = self.input_buffering {
+            game.set_input_buffering(buffer);
+        }
+
+        if let Some(delay) = self.das {
+            game.set_das(delay);
+        }
+
+        if let Some(rate) = self.arr {
+            game.set_arr(rate);
+        }
+
+        if let Some(enabled) = self.spawn_row_nudge {
+            game.set_spawn_row_nudge(enabled);
+        }
+
+        if let Some(enabled) = self.tracks_cell_ages {
+            game.set_tracks_cell_ages(enabled);
+        }
+
+        if let Some(rows) = self.danger_threshold {
+            game.set_danger_threshold(rows);
+        }
+
+        if let Some(palette) = self.palette {
+            game.set_palette(palette);
+        }
+
+        // Spawn the first piece again, so it follows the settings as well
+        game.reset();
+        game
+    }
+}
+
+/// Creates the randomizer used when none was given.
+#[cfg(feature = "std")]
+fn default_randomizer() -> Box<dyn Randomizer> {
+    Box::new(SevenBag::new())
+}
+
+/// Creates the randomizer used when none was given.
+#[cfg(not(feature = "std"))]
+fn default_randomizer() -> Box<dyn Randomizer> {
+    Box::new(SevenBag::with_seed(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pieces::{PieceData, PieceKind};
+
+    use alloc::vec::Vec;
+
+    #[test]
+    fn every_setting_takes_effect() {
+        let game = GameBuilder::new()
+            .seed(42)
+            .size(12, 24)
+            .scale_factor(2)
+            .preview(4)
+            .hold_capacity(2)
+            .lock_mode(LockMode::Classic)
+            .gravity(3.0)
+            .are(0.125)
+            .input_buffering(true)
+            .das(0.25)
+            .arr(0.0625)
+            .spawn_row_nudge(false)
+            .tracks_cell_ages(true)
+            .danger_threshold(8)
+            .palette(Palette::monochrome())
+            .build();
+
+        assert_eq!(game.scale_factor(), 2);
+        assert_eq!(game.playfield().config(), PlayfieldConfig { width: 6, height: 12 });
+        assert_eq!(game.display_playfield().config(), PlayfieldConfig { width: 12, height: 24 });
+        assert_eq!(game.preview_count(), 4);
+        assert_eq!(game.next_pieces().len(), 4);
+        assert_eq!(game.hold_capacity(), 2);
+        assert_eq!(game.lock_mode(), LockMode::Classic);
+        assert_eq!(game.gravity(), 3.0);
+        assert_eq!(game.are(), 0.125);
+        assert!(game.input_buffering());
+        assert_eq!(game.das(), 0.25);
+        assert_eq!(game.arr(), 0.0625);
+        assert!(!game.spawn_row_nudge());
+        assert!(game.playfield().tracks_cell_ages());
+        assert_eq!(game.danger_threshold(), Some(8));
+        assert_eq!(game.palette(), &Palette::monochrome());
+
+        // The seed gives the same pieces as a seeded game
+        let seeded = Game::with_seed(42);
+        let kinds = |game: &Game| -> Vec<PieceKind> {
+            core::iter::once(game.active_piece().kind()).chain(game.next_pieces().iter().map(PieceData::kind)).take(5).collect()
+        };
+        assert_eq!(kinds(&game), kinds(&seeded));
+    }
+
+    #[test]
+    fn lock_delay_takes_effect() {
+        let mut game = GameBuilder::new().seed(0).lock_delay(0.25).build();
+        game.sonic_drop();
+
+        assert!(game.tick(0.125).is_none());
+        assert!(game.tick(0.125).is_some());
+    }
+
+    #[test]
+    fn defaults_match_a_new_game() {
+        let built = GameBuilder::new().seed(7).build();
+        let seeded = Game::with_seed(7);
+
+        assert_eq!(built.playfield().config(), PlayfieldConfig::guideline());
+        assert_eq!(built.preview_count(), seeded.preview_count());
+        assert_eq!(built.hold_capacity(), seeded.hold_capacity());
+        assert_eq!(built.lock_mode(), seeded.lock_mode());
+        assert_eq!(built.active_piece().kind(), seeded.active_piece().kind());
+    }
+}
@@ -0,0 +1,123 @@
+//! Defines the events a game reports to observers.
+
+use crate::game::GameOverReason;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Something that happened in a game, reported to the handler set with [`Game::set_event_handler()`].
+///
+/// [`Game::set_event_handler()`]: crate::game::Game::set_event_handler
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// A new active piece was placed on the field.
+    PieceSpawned,
+
+    /// The active piece was copied into the playfield.
+    PieceLocked,
+
+    /// Completed lines were removed from the playfield.
+    LinesCleared {
+        /// The amount of cleared lines.
+        count: usize,
+
+        /// The indices of the cleared rows in the playfield, including its non-visible part, in ascending order.
+        /// Subtract [`Playfield::visible_rows()`]`.start` to get the row within the visible area,
+        /// which is what [`ClearResult::cleared_rows`] reports.
+        ///
+        /// [`Playfield::visible_rows()`]: crate::game::Playfield::visible_rows
+        /// [`ClearResult::cleared_rows`]: crate::score::ClearResult::cleared_rows
+        rows: Vec<usize>
+    },
+
+    /// The active piece was put into the hold slot.
+    PieceHeld,
+
+    /// The game is over.
+    GameOver {
+        /// Why the game ended.
+        reason: GameOverReason
+    },
+
+    /// The active piece was rotated.
+    Rotated {
+        /// Whether one of the kick tests was needed for the rotation to succeed.
+        kick_used: bool
+    },
+
+    /// The stack reached the danger threshold or fell back below it.
+    ///
+    /// See [`Game::set_danger_threshold()`].
+    ///
+    /// [`Game::set_danger_threshold()`]: crate::game::Game::set_danger_threshold
+    DangerChanged {
+        /// Whether the stack is in danger now.
+        in_danger: bool
+    }
+}
+
+/// A handler that is called with every event of a game.
+pub type EventHandler = Box<dyn FnMut(&GameEvent)>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Playfield};
+    use crate::pieces::PieceKind;
+    use crate::randomizer::{FixedSequence, SequenceEnd};
+
+    use alloc::rc::Rc;
+    use alloc::vec;
+    use core::cell::RefCell;
+
+    #[test]
+    fn handler_receives_events_in_order() {
+        let sequence = vec![PieceKind::T, PieceKind::I, PieceKind::O];
+        let mut game = Game::with_randomizer(Box::new(FixedSequence::new(sequence, SequenceEnd::RepeatLast)));
+        *game.playfield_mut() = Playfield::from_ascii("###....###").unwrap();
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&log);
+        game.set_event_handler(Box::new(move |event| recorder.borrow_mut().push(event.clone())));
+
+        game.rotate_right();
+        game.hold_piece();
+        game.hard_drop();
+
+        let bottom = game.playfield().true_height() - 1;
+        assert_eq!(*log.borrow(), [
+            GameEvent::Rotated { kick_used: false },
+            GameEvent::PieceHeld,
+            GameEvent::PieceSpawned,
+            GameEvent::PieceLocked,
+            GameEvent::LinesCleared { count: 1, rows: vec![bottom] },
+            GameEvent::PieceSpawned
+        ]);
+    }
+
+    #[test]
+    fn danger_is_reported_once_each_way() {
+        let mut game = Game::with_randomizer(Box::new(FixedSequence::new(vec![PieceKind::I], SequenceEnd::RepeatLast)));
+        *game.playfield_mut() = Playfield::from_ascii(&".#########\n".repeat(4)).unwrap();
+        game.set_danger_threshold(5);
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&log);
+        game.set_event_handler(Box::new(move |event| {
+            if let GameEvent::DangerChanged { in_danger } = event {
+                recorder.borrow_mut().push(*in_danger);
+            }
+        }));
+
+        // Two flat I-pieces on top of the stack reach and then pass the threshold
+        game.hard_drop();
+        game.hard_drop();
+        assert_eq!(*log.borrow(), [true]);
+
+        // A vertical I-piece in the open column clears the four garbage rows
+        game.rotate_right();
+        while game.move_left() {}
+        assert_eq!(game.hard_drop().map(|result| result.lines), Some(4));
+        assert_eq!(*log.borrow(), [true, false]);
+    }
+}
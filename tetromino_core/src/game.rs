@@ -1,57 +1,331 @@
 //! Defines the core game logic.
 
+use crate::event::{EventHandler, GameEvent};
+use crate::input::{GameInput, HorizontalDir};
 use crate::misc::{Color, Vec2I8};
-use crate::pieces::{PieceData, PieceBoolMatrix, PIECE_COUNT};
+use crate::palette::Palette;
+use crate::pieces::{Orientation, PieceData, PieceBoolMatrix, PieceKind, PIECE_COUNT};
+use crate::randomizer::{Randomizer, SevenBag};
+use crate::score::{Score, ClearKind, ClearResult};
 
-use std::collections::VecDeque;
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Index, IndexMut, Range};
+use rand::RngCore;
 
-/// The width of the playfield in blocks.
+/// The width of the guideline playfield in blocks.
 pub const PLAYFIELD_WIDTH: usize = 10;
 
-/// The height of the playfield in blocks.
+/// The height of the guideline playfield in blocks.
 /// The true height is double this value and only the "high" half is visible.
 pub const PLAYFIELD_HEIGHT: usize = 20;
 
-/// The "true" possible height of the playfield in blocks including the non-visible parts.
+/// The "true" possible height of the guideline playfield in blocks including the non-visible parts.
 pub const TRUE_PLAYFIELD_HEIGHT: usize = PLAYFIELD_HEIGHT * 2;
 
+/// Describes the dimensions of a playfield.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PlayfieldConfig {
+    /// The width of the playfield in blocks.
+    pub width: usize,
+    /// The visible height of the playfield in blocks.
+    /// The true height is double this value and only the "high" half is visible.
+    pub height: usize,
+}
+
+/// The default time in seconds a piece may rest on the stack before it locks.
+pub const DEFAULT_LOCK_DELAY: f64 = 0.5;
+
+/// How often moving or rotating a resting piece may reset its lock delay.
+pub const MAX_LOCK_RESETS: u32 = 15;
+
+/// The default delay in seconds before a held direction starts repeating.
+pub const DEFAULT_DAS: f64 = 0.167;
+
+/// The default time in seconds between repeated moves of a held direction.
+pub const DEFAULT_ARR: f64 = 0.033;
+
+/// How many rows the stack has to fall below the danger threshold before the danger is over again.
+pub const DANGER_HYSTERESIS: usize = 2;
+
+/// The highest level that still speeds up the guideline gravity.
+pub const MAX_GRAVITY_LEVEL: u32 = 20;
+
+/// The default amount of upcoming pieces that are known in advance.
+pub const DEFAULT_PREVIEW_COUNT: usize = 8;
+
+/// The widest playfield that can be converted with [`Playfield::to_bitboard()`].
+pub const MAX_BITBOARD_WIDTH: usize = 16;
+
+/// The color of garbage tiles added with [`Playfield::add_garbage_lines()`].
+pub const GARBAGE_COLOR: Color = Color::new(0x9a, 0x9a, 0x9a);
+
 /// Represents an active tetromino game.
 pub struct Game {
-    playfield: Playfield,
+    pub(crate) playfield: Playfield,
     active_piece: ActivePiece,
-    next_pieces: VecDeque<PieceData>,
-    held_piece: Option<PieceData>,
+    pub(crate) next_pieces: VecDeque<PieceData>,
+    held_pieces: Vec<PieceData>,
+    hold_capacity: usize,
+    preview_count: usize,
+    scale_factor: usize,
+    spawn_row_nudge: bool,
     used_hold: bool,
-    rng: RandomGenerator,
+    last_move_was_rotation: bool,
+    lock_delay: f64,
+    lock_timer: f64,
+    lock_resets: u32,
+    lock_mode: LockMode,
+    lock_pending: bool,
+    are: f64,
+    are_timer: Option<f64>,
+    input_buffering: bool,
+    input_buffer: Vec<GameInput>,
+    game_over_reason: Option<GameOverReason>,
+    danger_threshold: Option<usize>,
+    in_danger: bool,
+    is_paused: bool,
+    das: f64,
+    arr: f64,
+    auto_shift: Option<AutoShift>,
+    gravity: Option<f64>,
+    gravity_progress: f64,
+    pub(crate) pieces: [PieceData; PIECE_COUNT],
+    palette: Palette,
+    randomizer: Box<dyn Randomizer>,
+    initial_randomizer: Box<dyn Randomizer>,
+    pieces_drawn: u64,
+    score: Score,
+    piece_counts: [u32; PIECE_COUNT],
+    pieces_placed: u32,
+    elapsed_seconds: f64,
+    event_handler: Option<EventHandler>,
+}
+
+/// The direction being auto-shifted and the time until its next move.
+#[derive(Copy, Clone)]
+struct AutoShift {
+    dir: HorizontalDir,
+    delay: f64,
 }
 
 /// Represents an active, falling piece in the game.
 ///
 /// This is mostly a transparent struct and its methods are only helpers.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ActivePiece {
     pub piece_data: PieceData,
     pub rotation: usize,
     pub position: Vec2I8
 }
 
+/// A final resting spot of the active piece, given by its rotation and position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Placement {
+    pub rotation: usize,
+    pub position: Vec2I8
+}
+
+/// Decides when a piece resting on the stack locks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LockMode {
+    /// A resting piece locks once the lock delay expires, which moving or rotating it can reset a limited
+    /// amount of times.
+    ExtendedDelay,
+
+    /// A resting piece locks on the next tick after it failed to move down, either by gravity or soft drop.
+    /// The lock delay is not used.
+    Classic
+}
+
+/// What a tile of the playfield is filled with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CellKind {
+    /// The tile is empty.
+    Empty,
+
+    /// The tile was locked as part of a piece of the given kind.
+    Piece(PieceKind),
+
+    /// The tile is set, but not from a piece, such as garbage.
+    Garbage
+}
+
+/// The reason a [`Placement`] could not be applied.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlacementError {
+    /// The rotation is not in [0..=3].
+    InvalidRotation,
+
+    /// The piece would overlap set tiles or leave the playfield.
+    Overlaps,
+
+    /// The piece would not rest on the stack or floor.
+    NotResting,
+
+    /// The game is waiting out the entry delay, so there is no piece to place.
+    EntryDelay,
+
+    /// The game is paused.
+    Paused,
+
+    /// The game is over.
+    GameOver
+}
+
+/// What a piece collides with, as determined by [`Playfield::collision_kind()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CollisionKind {
+    /// The piece reaches past the walls, the floor, or the top of the playfield.
+    Wall,
+
+    /// The piece overlaps set tiles.
+    Block
+}
+
+/// Why a game ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameOverReason {
+    /// A new piece overlapped the stack at its spawn position.
+    BlockOut,
+
+    /// A piece locked entirely above the visible part of the playfield.
+    LockOut,
+
+    /// Garbage pushed the stack into the active piece.
+    TopOut
+}
+
+/// What happened when calling [`Game::hold_piece()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HoldOutcome {
+    /// The active piece was swapped with the held piece of the given kind, which is now active.
+    Swapped(PieceKind),
+
+    /// Nothing was held yet, so the next piece of the given kind was taken from the queue and is now active.
+    FilledFromQueue(PieceKind),
+
+    /// Holding is not possible right now, so nothing changed.
+    Blocked
+}
+
+/// The reason an ASCII grid could not be parsed by [`Playfield::from_ascii()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlayfieldParseError {
+    /// A row does not have as many tiles as the playfield is wide.
+    WidthMismatch { row: usize, expected: usize, found: usize },
+
+    /// There are more rows than the playfield is truly high.
+    TooManyRows { expected: usize, found: usize },
+
+    /// A character is neither `#` nor `.`.
+    InvalidChar { row: usize, column: usize, found: char }
+}
+
+/// A saved state of a game, created by [`Game::snapshot()`] and applied with [`Game::restore()`].
+///
+/// This includes the state of the randomizer, so a restored game continues with the same pieces.
+///
+/// With the `serde` feature, snapshots can be serialized, such as for save-states. Randomizers cannot be
+/// serialized, so only the amount of pieces drawn so far is stored. Restoring a deserialized snapshot
+/// replays that many draws on a copy of the randomizer the game was created with, so it has to be restored
+/// onto a game created with the same seed or randomizer and the same playfield dimensions.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSnapshot {
+    playfield: Playfield,
+    active_piece: ActivePiece,
+    next_pieces: VecDeque<PieceData>,
+    held_pieces: Vec<PieceData>,
+    used_hold: bool,
+    last_move_was_rotation: bool,
+    lock_timer: f64,
+    lock_resets: u32,
+    lock_pending: bool,
+    are_timer: Option<f64>,
+    input_buffer: Vec<GameInput>,
+    game_over_reason: Option<GameOverReason>,
+    in_danger: bool,
+    gravity_progress: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    randomizer: Option<Box<dyn Randomizer>>,
+    pieces_drawn: u64,
+    score: Score,
+    piece_counts: [u32; PIECE_COUNT],
+    pieces_placed: u32,
+    elapsed_seconds: f64,
+}
+
 /// Represents an active playfield.
+///
+/// Playfields are equal if all their tiles have the exact same colors. The lock times are not compared.
+/// Use [`Playfield::occupancy_eq()`] to only compare which tiles are set.
+#[derive(Clone)]
 pub struct Playfield {
-    fill_state: [[Color; PLAYFIELD_WIDTH]; PLAYFIELD_HEIGHT * 2],
+    width: usize,
+    height: usize,
+    // Stored row by row, starting at the top
+    fill_state: Vec<Color>,
+    // Laid out like fill_state, None for tiles that are not part of a locked piece
+    piece_kinds: Vec<Option<PieceKind>>,
+    // Only tracked when enabled, laid out like fill_state
+    lock_times: Option<Vec<Option<f64>>>,
 }
 
-/// A random generator supplying the game with pieces.
+/// The character used for set tiles in the ASCII representation of a playfield.
+const ASCII_FILLED: char = '#';
+
+/// The character used for empty tiles in the ASCII representation of a playfield.
+const ASCII_EMPTY: char = '.';
+
+/// The indices into [`Playfield::corner_occupancy()`] of the corners the T-piece points towards, by rotation.
+const T_FRONT_CORNERS: [[usize; 2]; 4] = [[0, 1], [1, 3], [2, 3], [0, 2]];
+
+/// The inputs tried by [`Game::enumerate_placements()`] to move the piece before dropping it.
+const PLACEMENT_SEARCH_INPUTS: [GameInput; 5] = [
+    GameInput::MoveLeft,
+    GameInput::MoveRight,
+    GameInput::RotateCCW,
+    GameInput::RotateCW,
+    GameInput::Rotate180
+];
+
+/// The inputs tried by [`Game::path_to()`], which also soft drops to find tucks and spins.
+const PATH_SEARCH_INPUTS: [GameInput; 6] = [
+    GameInput::MoveLeft,
+    GameInput::MoveRight,
+    GameInput::RotateCCW,
+    GameInput::RotateCW,
+    GameInput::Rotate180,
+    GameInput::SoftDrop
+];
+
+/// Gets the guideline gravity in cells per second for a level.
 ///
-/// It starts by filling a bag with all known pieces in random order, then draining that bag in order.
-/// Once the bag is empty, it restarts.
-pub struct RandomGenerator {
-    rng: StdRng,
-    pieces: [PieceData; PIECE_COUNT],
-    bag: [usize; PIECE_COUNT],
-    bag_left: usize
+/// Levels past [`MAX_GRAVITY_LEVEL`] are treated as that level.
+pub fn guideline_gravity(level: u32) -> f64 {
+    let level = level.clamp(1, MAX_GRAVITY_LEVEL);
+    let base = 0.8 - (level - 1) as f64 * 0.007;
+
+    // Float math functions need std, so the power is multiplied out
+    let seconds_per_cell: f64 = (1..level).map(|_| base).product();
+    1.0 / seconds_per_cell
 }
 
+/// The kick tests to try when rotating by 180°, as [`PieceState`] only holds kicks for single steps.
+///
+/// [`PieceState`]: crate::pieces::PieceState
+const ROTATE_180_KICKS: [Vec2I8; 3] = [Vec2I8::new(0, -1), Vec2I8::new(1, 0), Vec2I8::new(-1, 0)];
+
 /// Helper function to negate kick test values.
 fn neg_kicks(src: &[Vec2I8; 4]) -> [Vec2I8; 4] {
     [-src[0], -src[1], -src[2], -src[3]]
@@ -60,381 +334,4085 @@ fn neg_kicks(src: &[Vec2I8; 4]) -> [Vec2I8; 4] {
 impl Game {
     /// Creates a new empty game state.
     /// An active piece has already been placed on the field.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
-        let mut slf = Self {
-            playfield: Playfield::new(),
-            active_piece: ActivePiece::new(PieceData::default(), Vec2I8::new(0, 0)),
-            next_pieces: VecDeque::new(),
-            held_piece: None,
-            used_hold: false,
-            rng: RandomGenerator::new(),
-        };
-
-        const NEXT_SIZE: usize = 8;
-
-        let first = slf.rng.next_piece().clone();
-        for _ in 0..NEXT_SIZE {
-            slf.next_pieces.push_back(slf.rng.next_piece().clone());
-        }
+        Game::with_randomizer(Box::new(SevenBag::new()))
+    }
 
-        slf.spawn_new_piece(first);
-        slf
+    /// Creates a new empty game state whose piece sequence is determined by a seed.
+    /// Games created with the same seed receive the same pieces in the same order.
+    pub fn with_seed(seed: u64) -> Self {
+        Game::with_randomizer(Box::new(SevenBag::with_seed(seed)))
     }
 
-    /// Tries to move the active piece left.
-    ///
-    /// Returns whether it succeeded.
-    pub fn move_left(&mut self) -> bool {
-        self.try_move(|p, _| p.x -= 1)
+    /// Creates a new empty game state whose pieces are drawn from a [`SevenBag`] shuffled with the given rng.
+    pub fn with_rng<R: RngCore + Clone + 'static>(rng: R) -> Self {
+        Game::with_randomizer(Box::new(SevenBag::with_rng(rng)))
     }
 
-    /// Tries to move the active piece right.
-    ///
-    /// Returns whether it succeeded.
-    pub fn move_right(&mut self) -> bool {
-        self.try_move(|p, _| p.x += 1)
+    /// Creates a new empty game state drawing pieces from the given randomizer.
+    pub fn with_randomizer(randomizer: Box<dyn Randomizer>) -> Self {
+        Game::with_config_and_randomizer(PlayfieldConfig::guideline(), randomizer)
     }
 
-    /// Tries to rotate the piece left.
-    ///
-    /// This attempts to make use of the SRS kick tests.
-    /// Returns whether any rotation succeeded.
-    pub fn rotate_left(&mut self) -> bool {
-        let cur_rot = self.active_piece.rotation;
-        let trg_rot = if cur_rot == 0 { 3 } else { cur_rot - 1 };
-        let kicks = neg_kicks(self.active_piece.piece_data.state(trg_rot).kick_tests());
-        self.try_move(|_, r| *r = trg_rot) || self.try_move_kicks(trg_rot, &kicks)
+    /// Creates a new empty game state with a playfield of the given dimensions.
+    #[cfg(feature = "std")]
+    pub fn with_config(config: PlayfieldConfig) -> Self {
+        Game::with_config_and_randomizer(config, Box::new(SevenBag::new()))
     }
 
-    /// Tries to rotate the piece right.
-    ///
-    /// This attempts to make use of the SRS kick tests.
-    /// Returns whether any rotation succeeded.
-    pub fn rotate_right(&mut self) -> bool {
-        let cur_rot = self.active_piece.rotation;
-        let trg_rot = if cur_rot == 3 { 0 } else { cur_rot + 1 };
-        let kicks = *self.active_piece.piece_data.state(cur_rot).kick_tests();
-        self.try_move(|_, r| *r = trg_rot) || self.try_move_kicks(trg_rot, &kicks)
+    /// Creates a new empty game state with a playfield of the given dimensions, drawing pieces from the given randomizer.
+    pub fn with_config_and_randomizer(config: PlayfieldConfig, mut randomizer: Box<dyn Randomizer>) -> Self {
+        let pieces = PieceData::create_all_pieces();
+        let initial_randomizer = randomizer.clone();
+
+        // Draw the first piece up front, so the active piece is a real one from the start
+        let first = randomizer.next_piece(&pieces);
+
+        let mut slf = Self {
+            playfield: Playfield::with_size(config),
+            active_piece: ActivePiece::new(first.clone(), Vec2I8::new(0, 0)),
+            next_pieces: VecDeque::new(),
+            held_pieces: Vec::with_capacity(1),
+            hold_capacity: 1,
+            preview_count: DEFAULT_PREVIEW_COUNT,
+            scale_factor: 1,
+            spawn_row_nudge: true,
+            used_hold: false,
+            last_move_was_rotation: false,
+            lock_delay: DEFAULT_LOCK_DELAY,
+            lock_timer: DEFAULT_LOCK_DELAY,
+            lock_resets: 0,
+            lock_mode: LockMode::ExtendedDelay,
+            lock_pending: false,
+            are: 0.0,
+            are_timer: None,
+            input_buffering: false,
+            input_buffer: Vec::new(),
+            game_over_reason: None,
+            danger_threshold: None,
+            in_danger: false,
+            is_paused: false,
+            das: DEFAULT_DAS,
+            arr: DEFAULT_ARR,
+            auto_shift: None,
+            gravity: None,
+            gravity_progress: 0.0,
+            pieces,
+            palette: Palette::guideline(),
+            initial_randomizer,
+            randomizer,
+            pieces_drawn: 1,
+            score: Score::new(),
+            piece_counts: [0; PIECE_COUNT],
+            pieces_placed: 0,
+            elapsed_seconds: 0.0,
+            event_handler: None,
+        };
+
+        slf.spawn_first_piece(first);
+        slf
     }
 
-    /// Tries to move the piece down.
+    /// Restarts the game with an empty playfield, keeping its settings and playfield dimensions.
     ///
-    /// Returns whether it succeeded.
-    /// If it fails, this indicates the piece has hit the bottom.
-    pub fn move_down(&mut self) -> bool {
-        self.try_move(|p, _| p.y += 1)
-    }
+    /// The game is unpaused and the randomizer is returned to the state the game was created with,
+    /// so the piece sequence starts over.
+    pub fn reset(&mut self) {
+        self.playfield.clear();
+        self.next_pieces.clear();
+        self.held_pieces.clear();
+        self.used_hold = false;
+        self.game_over_reason = None;
+        self.is_paused = false;
+        self.auto_shift = None;
+        self.are_timer = None;
+        self.input_buffer.clear();
+        self.randomizer = self.initial_randomizer.clone();
+        self.pieces_drawn = 0;
+        self.score = Score::new();
+        self.piece_counts = [0; PIECE_COUNT];
+        self.pieces_placed = 0;
+        self.elapsed_seconds = 0.0;
+        self.update_danger();
 
-    /// Drops the piece to the bottom in a single move.
-    pub fn quick_drop(&mut self) {
-        while self.move_down() {}
+        let first = self.draw_piece();
+        self.spawn_first_piece(first);
     }
 
-    /// Hold the currently active piece in the "hold" slot and swap in the held piece if there was one.
-    /// If no piece was held yet, puts in a new piece from the sequence.
+    /// Advances the game by `dt` seconds.
     ///
-    /// Returns whether it was successful. This fails if it had been used already without placing a piece down.
-    pub fn hold_piece(&mut self) -> bool {
-        if self.used_hold {
-            return false;
+    /// This first applies gravity to the active piece. Partial progress towards the next
+    /// row carries over to the next call.
+    /// While the active piece is resting on the stack, this counts down the lock delay
+    /// and locks the piece with [`Game::finish_piece_turn()`] once it expires.
+    /// With [`LockMode::Classic`], it instead locks a resting piece once moving it down has failed.
+    /// Returns the result of that lock if it happened and the game is not over.
+    ///
+    /// During the entry delay after a lock, this only counts down the [`Game::are()`] and then spawns the
+    /// next piece, followed by any inputs buffered in the meantime.
+    pub fn tick(&mut self, dt: f64) -> Option<ClearResult> {
+        if self.is_game_over() || self.is_paused {
+            return None;
         }
 
-        // Copy out the current piece
-        let to_hold = Some(self.active_piece.piece_data.clone());
+        self.elapsed_seconds += dt;
+        self.apply_auto_shift(dt);
 
-        if let Some(held) = self.held_piece.take() {
-            // If we already held a piece, swap it in
-            self.spawn_new_piece(held);
-        } else {
-            // Otherwise take one from the queue
-            let next_piece = self.pop_next_piece();
-            self.spawn_new_piece(next_piece);
+        if let Some(timer) = self.are_timer {
+            if timer > dt {
+                self.are_timer = Some(timer - dt);
+            } else {
+                self.are_timer = None;
+                self.finish_entry_delay();
+            }
+
+            return None;
         }
 
-        self.used_hold = true;
-        self.held_piece = to_hold;
-        true
-    }
+        let gravity = self.gravity();
+        if gravity.is_infinite() {
+            // 20G drops straight to the floor, since infinity times a zero dt would turn the progress into NaN
+            while self.move_down() {}
+            self.gravity_progress = 0.0;
+        } else {
+            self.gravity_progress += gravity * dt;
+            while self.gravity_progress >= 1.0 {
+                self.gravity_progress -= 1.0;
+                if !self.move_down() {
+                    // Resting pieces don't build up progress
+                    self.gravity_progress = 0.0;
+                }
+            }
+        }
 
-    /// Locks down the piece by copying it into the playfield and spawning a new one.
-    /// Additionally, full lines are cleared.
-    ///
-    /// If returning [`Some`], its value indicates the amount of cleared lines.
-    /// If returning [`None`], putting in the new piece failed, and the game is over.
-    pub fn finish_piece_turn(&mut self) -> Option<usize> {
-        self.lock_down_piece();
+        if !self.is_resting() {
+            self.lock_timer = self.lock_delay;
+            self.lock_pending = false;
+            return None;
+        }
 
-        // Place the next piece in
-        let next_piece = self.pop_next_piece();
-        self.used_hold = false;
+        if self.lock_mode == LockMode::Classic {
+            return if self.lock_pending {
+                self.finish_piece_turn()
+            } else {
+                None
+            };
+        }
 
-        let cleared = self.clear_completed_lines();
-        if self.spawn_new_piece(next_piece) {
-            Some(cleared)
+        self.lock_timer -= dt;
+        if self.lock_timer <= 0.0 {
+            self.finish_piece_turn()
         } else {
             None
         }
     }
 
-    /// Copies the active piece into the playfield with no further actions.
+    /// Gets the gravity in cells per second.
     ///
-    /// This is done automatically by [`Game::finish_piece_turn()`].
-    pub fn lock_down_piece(&mut self) {
-        self.playfield.copy_in_piece(&self.active_piece)
+    /// Unless set with [`Game::set_gravity()`], this is the [`guideline_gravity()`] of the current level.
+    pub fn gravity(&self) -> f64 {
+        self.gravity.unwrap_or_else(|| guideline_gravity(self.level()))
     }
 
-    /// Clears all completed lines and returns how many were cleared.
+    /// Gets how far gravity has progressed towards moving the active piece down the next row, from 0.0 to 1.0.
     ///
-    /// This is done automatically by [`Game::finish_piece_turn()`].
-    pub fn clear_completed_lines(&mut self) -> usize {
-        self.playfield.clear_completed_lines()
+    /// Renderers may use this to draw the active piece between rows. A resting piece always reports 0.0.
+    pub fn drop_progress(&self) -> f64 {
+        if self.is_resting() {
+            0.0
+        } else {
+            self.gravity_progress.clamp(0.0, 1.0)
+        }
     }
 
-    /// Gets the playfield.
-    pub fn playfield(&self) -> &Playfield {
-        &self.playfield
+    /// Sets a fixed gravity in cells per second, overriding the level-based default.
+    /// Use [`f64::INFINITY`] to have pieces drop to the floor instantly (20G).
+    pub fn set_gravity(&mut self, cells_per_second: f64) {
+        self.gravity = Some(cells_per_second);
     }
 
-    /// Gets the active piece.
-    pub fn active_piece(&self) -> &ActivePiece {
-        &self.active_piece
+    /// Gets when a piece resting on the stack locks.
+    pub fn lock_mode(&self) -> LockMode {
+        self.lock_mode
     }
 
-    /// Gets the queue of upcoming pieces.
-    pub fn next_pieces(&self) -> &VecDeque<PieceData> {
-        &self.next_pieces
+    /// Sets when a piece resting on the stack locks. The default is [`LockMode::ExtendedDelay`].
+    pub fn set_lock_mode(&mut self, mode: LockMode) {
+        self.lock_mode = mode;
+        self.lock_pending = false;
     }
 
-    /// Gets the held piece.
-    pub fn held_piece(&self) -> Option<&PieceData> {
-        self.held_piece.as_ref()
+    /// Gets the entry delay in seconds between locking a piece and spawning the next one, also known as ARE.
+    pub fn are(&self) -> f64 {
+        self.are
     }
 
-    /// Pops the next piece of the upcoming pieces.
-    fn pop_next_piece(&mut self) -> PieceData {
-        let next_piece = self.next_pieces.pop_front().expect("next_pieces queue cannot be empty");
-        self.next_pieces.push_back(self.rng.next_piece().clone());
-        next_piece
+    /// Sets the entry delay in seconds between locking a piece and spawning the next one, also known as ARE.
+    ///
+    /// The default is zero, which spawns the next piece right away. Otherwise, the locked piece stays the
+    /// active piece until the delay is over, but it cannot be controlled. Renderers should not draw it then,
+    /// and may use the time to animate cleared lines instead.
+    pub fn set_are(&mut self, seconds: f64) {
+        self.are = seconds;
     }
 
-    /// Spawns a new active piece onto the field, replacing the old one.
-    fn spawn_new_piece(&mut self, new_piece: PieceData) -> bool {
-        // Pick a central position above the playfield
-        let new_piece_size = new_piece.size();
-        let spawn_pos = match new_piece_size {
-            2 => Vec2I8::new(4, (PLAYFIELD_HEIGHT - 1) as i8),
-            3 | 4 => Vec2I8::new(3, (PLAYFIELD_HEIGHT - 1) as i8),
-            _ => panic!("Invalid piece size")
-        };
-
-        self.active_piece = ActivePiece::new(new_piece, spawn_pos);
-
-        if new_piece_size < 4 {
-            // If not I piece (only 4-size), try to move down 1 tile
-            self.move_down();
-        }
-
-        !self.playfield.has_overlap(&self.active_piece)
+    /// Determines whether the game is waiting out the entry delay before spawning the next piece.
+    pub fn is_in_are(&self) -> bool {
+        self.are_timer.is_some()
     }
 
-    /// Attempts to perform a movement action through the specified function.
-    fn try_move(&mut self, change: impl FnOnce(&mut Vec2I8, &mut usize)) -> bool {
-        // Keep a backup in case moving fails
-        let old_pos = self.active_piece.position;
-        let old_rot = self.active_piece.rotation;
+    /// Gets the time in seconds until the next piece spawns, or [`None`] if there is no entry delay right now.
+    pub fn are_remaining(&self) -> Option<f64> {
+        self.are_timer
+    }
 
-        // Apply the changes from the fn to the active state
-        change(&mut self.active_piece.position, &mut self.active_piece.rotation);
+    /// Determines whether inputs during the entry delay are buffered.
+    pub fn input_buffering(&self) -> bool {
+        self.input_buffering
+    }
 
-        // If it overlaps, we need to reset.
-        if self.playfield.has_overlap(&self.active_piece) {
-            self.active_piece.position = old_pos;
-            self.active_piece.rotation = old_rot;
-            false
-        } else {
-            true
+    /// Sets whether inputs passed to [`Game::handle_input()`] during the entry delay are buffered.
+    /// Otherwise, they are ignored. The default is `false`.
+    ///
+    /// Buffered inputs are applied the moment the next piece spawns: a buffered hold first (initial hold),
+    /// then all other inputs in order, such as an initial rotation. A direction held with
+    /// [`Game::hold_direction()`] charges during the entry delay and, once charged, moves the piece right away.
+    pub fn set_input_buffering(&mut self, buffer: bool) {
+        self.input_buffering = buffer;
+        if !buffer {
+            self.input_buffer.clear();
         }
     }
 
-    /// Attempts all SRS kick options until one succeeds or all were tried.
-    fn try_move_kicks(&mut self, trg_rot: usize, kick_tests: &[Vec2I8; 4]) -> bool {
-        for &t in kick_tests.iter() {
-            let c = |p: &mut Vec2I8, r: &mut usize| {
-                *r = trg_rot;
-                *p += t;
-            };
-
-            if self.try_move(c) {
-                return true;
-            }
-        }
+    /// Sets the time in seconds a piece may rest on the stack before it locks.
+    pub fn set_lock_delay(&mut self, seconds: f64) {
+        self.lock_delay = seconds;
+        self.lock_timer = self.lock_timer.min(seconds);
+    }
 
-        false
+    /// Determines whether the active piece is resting on the stack or floor and cannot move down.
+    pub fn is_resting(&self) -> bool {
+        self.drop_distance() == 0
     }
-}
 
-impl Default for Game {
-    fn default() -> Self {
-        Game::new()
+    /// Determines whether the game is over.
+    pub fn is_game_over(&self) -> bool {
+        self.game_over_reason.is_some()
     }
-}
 
-impl ActivePiece {
-    /// Creates a new active piece with the given piece data and a spawn position.
-    pub fn new(piece_data: PieceData, spawn_pos: Vec2I8) -> ActivePiece {
-        ActivePiece {
-            piece_data,
-            position: spawn_pos,
-            rotation: 0
-        }
+    /// Gets why the game is over, or [`None`] if it is not.
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.game_over_reason
     }
 
-    /// Gets the matrix that is currently in use based on its rotation.
-    pub fn matrix(&self) -> PieceBoolMatrix {
-        self.piece_data.state(self.rotation).matrix()
+    /// Determines whether the game is paused.
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
     }
-}
 
-impl Playfield {
-    /// Creates a new empty playfield. This means all its tiles are black.
-    pub fn new() -> Playfield {
-        Playfield {
-            fill_state: [[Color::BLACK; PLAYFIELD_WIDTH]; TRUE_PLAYFIELD_HEIGHT]
-        }
+    /// Pauses or resumes the game.
+    ///
+    /// While paused, [`Game::tick()`] does nothing, so gravity, the lock delay and the play time are frozen.
+    /// Player inputs, that is moving, rotating, dropping, holding and placing the piece, are ignored as well.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.is_paused = paused;
     }
 
-    /// Determines whether the filled playfield tiles overlap with the active piece.
-    pub fn has_overlap(&self, piece: &ActivePiece) -> bool {
-        let mat = piece.matrix();
-        let x_base = piece.position.x as usize;
-        let y_base = piece.position.y as usize;
+    /// Applies an action of the player by calling the matching method.
+    ///
+    /// Returns whether the input had any effect. Lines cleared by a hard drop are reported through events.
+    /// During the entry delay, the input is buffered if enabled with [`Game::set_input_buffering()`],
+    /// which counts as having an effect.
+    pub fn handle_input(&mut self, input: GameInput) -> bool {
+        if self.is_in_are() && self.input_buffering && !self.is_paused && !self.is_game_over() {
+            self.input_buffer.push(input);
+            return true;
+        }
 
-        // mat dimensions are 4x4
-        for x in 0..4 {
-            for y in 0..4 {
-                // Use wrapping_add to avoid overflow (negative numbers cast to unsigned)
-                if mat[x][y] && self.has_tile(x.wrapping_add(x_base), y.wrapping_add(y_base)) {
-                    return true;
-                }
+        match input {
+            GameInput::MoveLeft => self.move_left(),
+            GameInput::MoveRight => self.move_right(),
+            GameInput::SoftDrop => self.soft_drop(),
+            GameInput::HardDrop => {
+                // Hard drops always lock, unless the controls are locked
+                let locked = self.controls_locked();
+                self.hard_drop();
+                !locked
             }
+            GameInput::RotateCW => self.rotate_right(),
+            GameInput::RotateCCW => self.rotate_left(),
+            GameInput::Rotate180 => self.rotate_180(),
+            GameInput::Hold => self.hold_piece() != HoldOutcome::Blocked
         }
-
-        false
     }
 
-    /// Gets the color of a tile. If not in range, it is [`Color::WHITE`].
-    pub fn get_tile(&self, x: usize, y: usize) -> Color {
-        if Playfield::is_in_bounds(x, y) {
-            self.fill_state[y][x]
-        } else {
-            Color::WHITE
+    /// Determines whether [`Game::handle_input()`] would succeed for the given input, without changing the game.
+    pub fn can_move(&self, input: GameInput) -> bool {
+        match input {
+            GameInput::MoveLeft => self.can_shift(Vec2I8::new(-1, 0)),
+            GameInput::MoveRight => self.can_shift(Vec2I8::new(1, 0)),
+            GameInput::SoftDrop => self.can_shift(Vec2I8::new(0, 1)),
+            GameInput::HardDrop => !self.controls_locked(),
+            GameInput::RotateCW => self.can_rotate_cw(),
+            GameInput::RotateCCW => self.can_rotate_ccw(),
+            GameInput::Rotate180 => self.can_rotate_180(),
+            GameInput::Hold => self.can_hold()
         }
     }
 
-    /// Gets a mutable reference to the color of a tile.
-    pub fn get_tile_mut(&mut self, x: usize, y: usize) -> Option<&mut Color> {
-        if Playfield::is_in_bounds(x, y) {
-            Some(&mut self.fill_state[y][x])
-        } else {
-            None
-        }
+    /// Determines whether [`Game::rotate_right()`] would succeed, including kick tests.
+    pub fn can_rotate_cw(&self) -> bool {
+        let (trg_rot, kicks) = self.active_piece.right_rotation();
+        self.can_rotate(trg_rot, &kicks)
     }
 
-    /// Determines if a tile is set. That is to say, it is not black and in range.
-    pub fn has_tile(&self, x: usize, y: usize) -> bool {
-        !self.get_tile(x, y).is_black()
+    /// Determines whether [`Game::rotate_left()`] would succeed, including kick tests.
+    pub fn can_rotate_ccw(&self) -> bool {
+        let (trg_rot, kicks) = self.active_piece.left_rotation();
+        self.can_rotate(trg_rot, &kicks)
     }
 
-    /// Determines if a specified coordinate is in bpunds.
-    pub fn is_in_bounds(x: usize, y: usize) -> bool {
-        x < PLAYFIELD_WIDTH && y < TRUE_PLAYFIELD_HEIGHT
+    /// Determines whether [`Game::rotate_180()`] would succeed, including kick tests.
+    pub fn can_rotate_180(&self) -> bool {
+        let trg_rot = (self.active_piece.rotation + 2) % 4;
+        self.can_rotate(trg_rot, &ROTATE_180_KICKS)
     }
 
-    /// Copies an active piece into the playfield matrix.
-    pub fn copy_in_piece(&mut self, piece: &ActivePiece) {
-        let mat = piece.matrix();
-        let x_base = piece.position.x as usize;
-        let y_base = piece.position.y as usize;
-
-        // mat dimensions are 4x4
-        for x in 0..4 {
-            for y in 0..4 {
-                // If the matrix has a tile, override the playfield's tile's color
-                if mat[x][y] {
-                    // Use wrapping_add to avoid overflow (negative numbers cast to unsigned)
-                    if let Some(m) = self.get_tile_mut(x.wrapping_add(x_base), y.wrapping_add(y_base)) {
-                        *m = piece.piece_data.color();
-                    }
-                }
-            }
-        }
+    /// Determines whether [`Game::hold_piece()`] would swap out the active piece.
+    pub fn can_hold(&self) -> bool {
+        !self.used_hold && !self.controls_locked()
     }
 
-    /// Clears all completed lines, returning the amount of lines that were cleared.
-    pub fn clear_completed_lines(&mut self) -> usize {
-        let mut cnt = 0;
-
-        // Move UP in index
-        for y in 0..TRUE_PLAYFIELD_HEIGHT {
-            let mut row_full = true;
-            for x in 0..PLAYFIELD_WIDTH {
-                if !self.has_tile(x, y) {
-                    row_full = false;
-                    break;
-                }
-            }
-
-            if row_full {
-                cnt += 1;
+    /// Gets the delay in seconds before a held direction starts repeating.
+    pub fn das(&self) -> f64 {
+        self.das
+    }
 
-                for yc in (1..=y).rev() {
-                    self.fill_state[yc] = self.fill_state[yc - 1];
-                }
+    /// Sets the delay in seconds before a held direction starts repeating.
+    pub fn set_das(&mut self, delay: f64) {
+        self.das = delay;
+    }
 
-                self.fill_state[0] = [Color::BLACK; PLAYFIELD_WIDTH];
-            }
-        }
+    /// Gets the time in seconds between repeated moves of a held direction.
+    pub fn arr(&self) -> f64 {
+        self.arr
+    }
 
-        cnt
+    /// Sets the time in seconds between repeated moves of a held direction.
+    /// Zero moves the piece all the way to the wall at once.
+    pub fn set_arr(&mut self, rate: f64) {
+        self.arr = rate;
     }
-}
 
-impl Default for Playfield {
-    fn default() -> Self {
-        Playfield::new()
+    /// Starts holding a direction, replacing any direction held before.
+    ///
+    /// The piece is moved once immediately. After [`Game::das()`] seconds, [`Game::tick()`] keeps moving it
+    /// every [`Game::arr()`] seconds until the direction is released.
+    /// Returns whether the first move succeeded.
+    pub fn hold_direction(&mut self, dir: HorizontalDir) -> bool {
+        self.auto_shift = Some(AutoShift { dir, delay: self.das });
+        self.shift(dir)
     }
-}
 
-impl RandomGenerator {
-    /// Creates a new random generator with an empty bag.
-    pub fn new() -> RandomGenerator {
-        RandomGenerator {
-            rng: StdRng::from_entropy(),
-            pieces: PieceData::create_all_pieces(),
-            bag: [0; 7],
-            bag_left: 0
+    /// Stops holding a direction. Does nothing if the other direction is held instead.
+    pub fn release_direction(&mut self, dir: HorizontalDir) {
+        if self.held_direction() == Some(dir) {
+            self.auto_shift = None;
         }
     }
 
-    /// Gets the next piece from the bag. The bag is automatically refilled when needed.
-    pub fn next_piece(&mut self) -> &PieceData {
-        if self.bag_left > 0 {
-            self.bag_left -= 1;
-            &self.pieces[self.bag[self.bag_left]]
-        } else {
-            let mut new_bag = vec![0, 1, 2, 3, 4, 5, 6];
-            for i in 0..new_bag.len() {
-                self.bag[i] = new_bag.remove(self.rng.gen_range(0..new_bag.len()));
-            }
-
-            self.bag_left = 6;
-            &self.pieces[self.bag[6]]
-        }
+    /// Gets the direction that is currently held, if any.
+    pub fn held_direction(&self) -> Option<HorizontalDir> {
+        self.auto_shift.map(|s| s.dir)
     }
-}
 
-impl Default for RandomGenerator {
-    fn default() -> Self {
-        RandomGenerator::new()
+    /// Tries to move the active piece left.
+    ///
+    /// Returns whether it succeeded.
+    pub fn move_left(&mut self) -> bool {
+        let moved = self.try_move(|p, _| p.x -= 1);
+        self.apply_move_reset(moved)
+    }
+
+    /// Tries to move the active piece right.
+    ///
+    /// Returns whether it succeeded.
+    pub fn move_right(&mut self) -> bool {
+        let moved = self.try_move(|p, _| p.x += 1);
+        self.apply_move_reset(moved)
+    }
+
+    /// Tries to rotate the piece left.
+    ///
+    /// This attempts to make use of the SRS kick tests.
+    /// Returns whether any rotation succeeded.
+    pub fn rotate_left(&mut self) -> bool {
+        let (trg_rot, kicks) = self.active_piece.left_rotation();
+        self.try_rotate(trg_rot, &kicks)
+    }
+
+    /// Tries to rotate the piece right.
+    ///
+    /// This attempts to make use of the SRS kick tests.
+    /// Returns whether any rotation succeeded.
+    pub fn rotate_right(&mut self) -> bool {
+        let (trg_rot, kicks) = self.active_piece.right_rotation();
+        self.try_rotate(trg_rot, &kicks)
+    }
+
+    /// Tries to rotate the piece by 180°.
+    ///
+    /// This attempts a fixed set of kicks: up one row first, then one column to either side.
+    /// Returns whether any rotation succeeded.
+    pub fn rotate_180(&mut self) -> bool {
+        let trg_rot = (self.active_piece.rotation + 2) % 4;
+        self.try_rotate(trg_rot, &ROTATE_180_KICKS)
+    }
+
+    /// Tries to move the piece down.
+    ///
+    /// Returns whether it succeeded.
+    /// If it fails, this indicates the piece has hit the bottom.
+    pub fn move_down(&mut self) -> bool {
+        let moved = self.try_move(|p, _| p.y += 1);
+        if moved {
+            // Falling further down restores the lock delay and its resets
+            self.lock_timer = self.lock_delay;
+            self.lock_resets = 0;
+        } else if self.lock_mode == LockMode::Classic && !self.controls_locked() {
+            self.lock_pending = true;
+        }
+
+        moved
+    }
+
+    /// Moves the piece down by player input, awarding soft-drop points.
+    ///
+    /// Returns whether it succeeded.
+    pub fn soft_drop(&mut self) -> bool {
+        let moved = self.move_down();
+        if moved {
+            self.score.add_soft_drop(1);
+        }
+
+        moved
+    }
+
+    /// Drops the piece to the bottom in a single move, awarding hard-drop points.
+    ///
+    /// Returns the amount of rows the piece travelled.
+    pub fn quick_drop(&mut self) -> usize {
+        let mut distance = 0;
+        while self.move_down() {
+            distance += 1;
+        }
+
+        self.score.add_hard_drop(distance);
+        distance
+    }
+
+    /// Drops the piece to the bottom without locking it, awarding soft-drop points for every row.
+    ///
+    /// The piece stays active and its lock delay starts over if it moved at all.
+    /// Returns the amount of rows the piece travelled.
+    pub fn sonic_drop(&mut self) -> usize {
+        let mut distance = 0;
+        while self.soft_drop() {
+            distance += 1;
+        }
+
+        distance
+    }
+
+    /// Drops the piece to the bottom and immediately locks it, awarding hard-drop points.
+    ///
+    /// This returns the same as [`Game::finish_piece_turn()`], except that [`None`] is returned without
+    /// doing anything while the game is paused, during the entry delay, or once the game is over.
+    pub fn hard_drop(&mut self) -> Option<ClearResult> {
+        if self.controls_locked() {
+            return None;
+        }
+
+        self.quick_drop();
+        self.finish_piece_turn()
+    }
+
+    /// Gets how many rows the active piece can still fall before it comes to rest.
+    pub fn drop_distance(&self) -> usize {
+        self.playfield.drop_distance(&self.active_piece)
+    }
+
+    /// Gets a copy of the active piece moved down to where it would land if dropped.
+    ///
+    /// If the active piece is already resting on the stack, this is equal to the active piece.
+    pub fn ghost_piece(&self) -> ActivePiece {
+        let mut ghost = self.active_piece.clone();
+        ghost.position.y += self.drop_distance() as i8;
+        ghost
+    }
+
+    /// Gets how many gravity steps remain before the active piece would lock in its current column.
+    ///
+    /// A gravity step is the `1 / gravity()` seconds it takes gravity to move the piece down one row.
+    /// This is the [`Game::drop_distance()`] plus the gravity steps that fit into the remaining lock delay.
+    /// With [`LockMode::Classic`], there is no lock delay, so it is the drop distance plus the one step that
+    /// fails to move the piece down and locks it, or zero if that step already happened.
+    ///
+    /// With infinite gravity, the drop happens within a single tick and counts as no steps. Without any
+    /// gravity, steps never come, so a piece that isn't resting never locks and this returns [`usize::MAX`].
+    /// In both cases, the lock delay has no length in steps and counts as one step while any of it remains.
+    pub fn steps_to_lock(&self) -> usize {
+        let gravity = self.gravity();
+        let no_gravity = gravity <= 0.0;
+        if no_gravity && !self.is_resting() {
+            return usize::MAX;
+        }
+
+        let drop_steps = if gravity.is_finite() {
+            self.drop_distance()
+        } else {
+            0
+        };
+
+        if self.lock_mode == LockMode::Classic {
+            return if self.lock_pending {
+                0
+            } else if no_gravity {
+                // Only gravity makes the step that fails to move the piece down
+                usize::MAX
+            } else {
+                drop_steps.saturating_add(1)
+            };
+        }
+
+        if self.lock_timer <= 0.0 {
+            return drop_steps;
+        }
+
+        if no_gravity || !gravity.is_finite() {
+            return drop_steps.saturating_add(1);
+        }
+
+        // Float math functions need std, so this rounds up by hand. Casts saturate and truncate towards zero.
+        let lock_time_steps = self.lock_timer * gravity;
+        let mut lock_steps = lock_time_steps as usize;
+        if (lock_steps as f64) < lock_time_steps {
+            lock_steps = lock_steps.saturating_add(1);
+        }
+
+        drop_steps.saturating_add(lock_steps)
+    }
+
+    /// Lists every distinct spot the active piece can come to rest in, reachable from its
+    /// current state by moving sideways and rotating (including kicks), followed by a hard drop.
+    ///
+    /// Placements that cover the same tiles are only listed once. This does not change the game.
+    pub fn enumerate_placements(&self) -> Vec<Placement> {
+        let mut visited = BTreeSet::new();
+        let mut landed_cells = BTreeSet::new();
+        let mut pending = VecDeque::new();
+        let mut placements = Vec::new();
+
+        visited.insert(Placement::of(&self.active_piece));
+        pending.push_back(self.active_piece.clone());
+
+        while let Some(piece) = pending.pop_front() {
+            // Record where this state lands when dropped
+            let mut landed = piece.clone();
+            landed.position.y += self.playfield.drop_distance(&piece) as i8;
+            if landed_cells.insert(landed.occupied_cells().collect::<Vec<_>>()) {
+                placements.push(Placement::of(&landed));
+            }
+
+            // Then queue up all states reachable by a single move
+            let neighbors = PLACEMENT_SEARCH_INPUTS.iter()
+                .filter_map(|&input| self.moved_piece(&piece, input));
+
+            for next in neighbors {
+                if visited.insert(Placement::of(&next)) {
+                    pending.push_back(next);
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Finds the shortest sequence of inputs that moves the active piece from its current state into
+    /// the placement, ending with a hard drop. This does not change the game.
+    ///
+    /// Soft drops are considered as well, so tucks and spins are found. Placements that cover the same
+    /// tiles as the target are treated as equal to it.
+    /// Returns [`None`] if the placement is not a resting spot the piece can reach.
+    pub fn path_to(&self, target: Placement) -> Option<Vec<GameInput>> {
+        self.path_from(&self.active_piece, target)
+    }
+
+    /// Finds the shortest sequence of inputs that moves the active piece from its spawn position into
+    /// the placement, ending with a hard drop, like [`Game::path_to()`]. This does not change the game.
+    ///
+    /// This is the input sequence with optimal finesse, which a trainer can compare the player's inputs against.
+    /// It starts from where the active piece spawned, no matter how it was moved since, but against the current playfield.
+    /// Returns an empty sequence if the placement is not a resting spot the piece can reach.
+    pub fn finesse_optimal(&self, target: Placement) -> Vec<GameInput> {
+        let spawned = self.create_spawned_piece(self.active_piece.piece_data.clone());
+        self.path_from(&spawned, target).unwrap_or_default()
+    }
+
+    /// Finds the shortest sequence of inputs that moves a piece from its given state into the placement,
+    /// ending with a hard drop. See [`Game::path_to()`].
+    fn path_from(&self, start_piece: &ActivePiece, target: Placement) -> Option<Vec<GameInput>> {
+        if target.rotation >= 4 {
+            return None;
+        }
+
+        let mut target_piece = start_piece.clone();
+        target_piece.rotation = target.rotation;
+        target_piece.position = target.position;
+        if self.playfield.has_overlap(&target_piece) || self.playfield.drop_distance(&target_piece) != 0 {
+            return None;
+        }
+
+        let target_cells: Vec<_> = target_piece.occupied_cells().collect();
+
+        // Maps every visited state to the state and input it was first reached from
+        let start = Placement::of(start_piece);
+        let mut came_from = BTreeMap::new();
+        let mut pending = VecDeque::new();
+        pending.push_back(start_piece.clone());
+
+        while let Some(piece) = pending.pop_front() {
+            let mut landed = piece.clone();
+            landed.position.y += self.playfield.drop_distance(&piece) as i8;
+            if landed.occupied_cells().eq(target_cells.iter().copied()) {
+                // Walk back to the start to collect the inputs
+                let mut path = vec![GameInput::HardDrop];
+                let mut current = Placement::of(&piece);
+                while current != start {
+                    let (previous, input) = came_from[&current];
+                    path.push(input);
+                    current = previous;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            let neighbors = PATH_SEARCH_INPUTS.iter()
+                .filter_map(|&input| self.moved_piece(&piece, input).map(|next| (input, next)));
+
+            for (input, next) in neighbors {
+                let placement = Placement::of(&next);
+                if placement != start && !came_from.contains_key(&placement) {
+                    came_from.insert(placement, (Placement::of(&piece), input));
+                    pending.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Moves the active piece straight into a placement and locks it there, skipping individual moves.
+    ///
+    /// The placement must be a legal resting spot, but it is not checked whether it is reachable.
+    /// If it is not legal, or the player could not control the piece right now, an error is returned
+    /// and the game is left unchanged.
+    /// Otherwise, this returns the same as [`Game::finish_piece_turn()`].
+    pub fn apply_placement(&mut self, placement: Placement) -> Result<Option<ClearResult>, PlacementError> {
+        if self.is_game_over() {
+            return Err(PlacementError::GameOver);
+        }
+
+        if self.is_paused {
+            return Err(PlacementError::Paused);
+        }
+
+        if self.is_in_are() {
+            return Err(PlacementError::EntryDelay);
+        }
+
+        if placement.rotation >= 4 {
+            return Err(PlacementError::InvalidRotation);
+        }
+
+        let mut piece = self.active_piece.clone();
+        piece.rotation = placement.rotation;
+        piece.position = placement.position;
+
+        if self.playfield.has_overlap(&piece) {
+            return Err(PlacementError::Overlaps);
+        }
+
+        if self.playfield.drop_distance(&piece) != 0 {
+            return Err(PlacementError::NotResting);
+        }
+
+        self.active_piece = piece;
+        self.last_move_was_rotation = false;
+        Ok(self.finish_piece_turn())
+    }
+
+    /// Hold the currently active piece in the "hold" slot and swap in the held piece if there was one.
+    /// If no piece was held yet, puts in a new piece from the sequence.
+    ///
+    /// With a [`Game::hold_capacity()`] above 1, pieces are put in from the sequence until the hold is full.
+    /// After that, the piece that was held the longest is swapped in, so the held pieces cycle in order.
+    ///
+    /// Returns what happened. This is [`HoldOutcome::Blocked`] if it had been used already without placing
+    /// a piece down, while the game is paused, during the entry delay, or once the game is over.
+    /// If the piece that becomes active overlaps the stack at its spawn position, the game is over.
+    pub fn hold_piece(&mut self) -> HoldOutcome {
+        if !self.can_hold() {
+            return HoldOutcome::Blocked;
+        }
+
+        // Copy out the current piece
+        let to_hold = self.active_piece.piece_data.clone();
+        self.emit(GameEvent::PieceHeld);
+
+        let outcome = if self.held_pieces.len() >= self.hold_capacity {
+            // If the hold is full, swap in the oldest held piece
+            let held = self.held_pieces.remove(0);
+            let kind = held.kind();
+            if !self.spawn_new_piece(held) {
+                self.top_out(GameOverReason::BlockOut);
+            }
+
+            HoldOutcome::Swapped(kind)
+        } else {
+            // Otherwise take one from the queue
+            let next_piece = self.pop_next_piece();
+            let kind = next_piece.kind();
+            if !self.spawn_new_piece(next_piece) {
+                self.top_out(GameOverReason::BlockOut);
+            }
+
+            HoldOutcome::FilledFromQueue(kind)
+        };
+
+        self.used_hold = true;
+        self.held_pieces.push(to_hold);
+        outcome
+    }
+
+    /// Gets the piece that would become active if [`Game::hold_piece()`] was called now,
+    /// placed at its spawn position and rotation.
+    ///
+    /// Returns [`None`] if holding is currently not possible, as determined by [`Game::can_hold()`].
+    pub fn preview_after_hold(&self) -> Option<ActivePiece> {
+        if !self.can_hold() {
+            return None;
+        }
+
+        let piece = if self.held_pieces.len() >= self.hold_capacity {
+            self.held_pieces[0].clone()
+        } else {
+            self.next_pieces.front()?.clone()
+        };
+
+        Some(self.create_spawned_piece(piece))
+    }
+
+    /// Locks down the piece by copying it into the playfield and spawning a new one.
+    /// Additionally, full lines are cleared and the score is updated.
+    ///
+    /// If returning [`Some`], its value describes the cleared lines.
+    /// If returning [`None`], the piece locked entirely above the visible playfield or putting in the new piece
+    /// failed, and the game is over.
+    /// With an [entry delay](Game::set_are), the new piece is only put in once [`Game::tick()`] has waited it out.
+    /// During the entry delay, while paused, or once the game is over, this does nothing and returns [`None`].
+    pub fn finish_piece_turn(&mut self) -> Option<ClearResult> {
+        if self.controls_locked() {
+            return None;
+        }
+
+        let kind = self.detect_t_spin();
+        self.lock_down_piece();
+
+        // Locking entirely within the non-visible rows is a lock out
+        let visible_top = self.playfield.height() as i8;
+        if self.active_piece.occupied_cells().all(|c| c.y < visible_top) {
+            self.top_out(GameOverReason::LockOut);
+            return None;
+        }
+
+        self.used_hold = false;
+
+        let completed = self.playfield.find_completed_lines();
+        let garbage_cleared = self.playfield.count_garbage_cells(&completed);
+        let cleared_rows = completed.iter()
+            .filter_map(|&y| y.checked_sub(self.playfield.height()))
+            .collect();
+
+        let lines = self.clear_completed_lines();
+        let result = ClearResult {
+            lines,
+            cleared_rows,
+            garbage_cleared,
+            kind,
+            perfect_clear: lines > 0 && self.playfield.is_empty()
+        };
+
+        self.score.add_clear(&result);
+        self.update_danger();
+
+        if self.are > 0.0 {
+            self.are_timer = Some(self.are);
+            self.lock_pending = false;
+            return Some(result);
+        }
+
+        // Place the next piece in
+        if self.spawn_next_piece() {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Copies the active piece into the playfield with no further actions.
+    ///
+    /// This is done automatically by [`Game::finish_piece_turn()`].
+    pub fn lock_down_piece(&mut self) {
+        self.playfield.copy_in_piece_at(&self.active_piece, self.elapsed_seconds);
+        self.pieces_placed += 1;
+        self.emit(GameEvent::PieceLocked);
+    }
+
+    /// Clears all completed lines and returns how many were cleared.
+    ///
+    /// This is done automatically by [`Game::finish_piece_turn()`].
+    pub fn clear_completed_lines(&mut self) -> usize {
+        let rows = self.playfield.find_completed_lines();
+        let count = rows.len();
+        self.playfield.remove_lines(&rows);
+
+        if count > 0 {
+            self.emit(GameEvent::LinesCleared { count, rows });
+        }
+
+        count
+    }
+
+    /// Sets the handler that is called with every [`GameEvent`] that happens from now on,
+    /// replacing any previous handler.
+    pub fn set_event_handler(&mut self, handler: EventHandler) {
+        self.event_handler = Some(handler);
+    }
+
+    /// Removes the event handler, if any.
+    pub fn clear_event_handler(&mut self) {
+        self.event_handler = None;
+    }
+
+    /// Captures the current state of the game, so it can be restored later.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            playfield: self.playfield.clone(),
+            active_piece: self.active_piece.clone(),
+            next_pieces: self.next_pieces.clone(),
+            held_pieces: self.held_pieces.clone(),
+            used_hold: self.used_hold,
+            last_move_was_rotation: self.last_move_was_rotation,
+            lock_timer: self.lock_timer,
+            lock_resets: self.lock_resets,
+            lock_pending: self.lock_pending,
+            are_timer: self.are_timer,
+            input_buffer: self.input_buffer.clone(),
+            game_over_reason: self.game_over_reason,
+            in_danger: self.in_danger,
+            gravity_progress: self.gravity_progress,
+            randomizer: Some(self.randomizer.clone()),
+            pieces_drawn: self.pieces_drawn,
+            score: self.score.clone(),
+            piece_counts: self.piece_counts,
+            pieces_placed: self.pieces_placed,
+            elapsed_seconds: self.elapsed_seconds,
+        }
+    }
+
+    /// Restores a previously captured state of the game.
+    ///
+    /// Settings, such as the lock delay and gravity, are left unchanged.
+    /// See [`GameSnapshot`] on restoring snapshots that were deserialized.
+    pub fn restore(&mut self, snapshot: GameSnapshot) {
+        self.playfield = snapshot.playfield;
+        self.active_piece = snapshot.active_piece;
+        self.next_pieces = snapshot.next_pieces;
+        self.held_pieces = snapshot.held_pieces;
+        self.used_hold = snapshot.used_hold;
+        self.last_move_was_rotation = snapshot.last_move_was_rotation;
+        self.lock_timer = snapshot.lock_timer;
+        self.lock_resets = snapshot.lock_resets;
+        self.lock_pending = snapshot.lock_pending;
+        self.are_timer = snapshot.are_timer;
+        self.input_buffer = snapshot.input_buffer;
+        self.game_over_reason = snapshot.game_over_reason;
+        self.in_danger = snapshot.in_danger;
+        self.gravity_progress = snapshot.gravity_progress;
+        self.randomizer = match snapshot.randomizer {
+            Some(randomizer) => randomizer,
+            None => {
+                // Deserialized snapshots do not hold the randomizer, so its draws are replayed
+                let mut randomizer = self.initial_randomizer.clone();
+                for _ in 0..snapshot.pieces_drawn {
+                    randomizer.next_piece(&self.pieces);
+                }
+
+                randomizer
+            }
+        };
+        self.pieces_drawn = snapshot.pieces_drawn;
+        self.score = snapshot.score;
+        self.piece_counts = snapshot.piece_counts;
+        self.pieces_placed = snapshot.pieces_placed;
+        self.elapsed_seconds = snapshot.elapsed_seconds;
+    }
+
+    /// Pushes garbage lines in from the bottom of the playfield, each with a hole in the given column.
+    /// The column is handled like in [`Playfield::add_garbage_lines()`].
+    ///
+    /// Returns whether the active piece now overlaps the stack, in which case the game is over.
+    pub fn receive_garbage(&mut self, count: usize, hole_column: usize) -> bool {
+        self.playfield.add_garbage_lines(count, hole_column);
+        self.update_danger();
+
+        if self.playfield.has_overlap(&self.active_piece) && !self.is_game_over() {
+            self.top_out(GameOverReason::TopOut);
+        }
+
+        self.is_game_over()
+    }
+
+    /// Gets the playfield.
+    pub fn playfield(&self) -> &Playfield {
+        &self.playfield
+    }
+
+    /// Gets which visible tiles of the playfield are set, as one bitmask per row.
+    /// See [`Playfield::to_bitboard()`].
+    pub fn board_as_bitboard(&self) -> Vec<u16> {
+        self.playfield.to_bitboard()
+    }
+
+    /// Gets the active piece.
+    pub fn active_piece(&self) -> &ActivePiece {
+        &self.active_piece
+    }
+
+    /// Gets the playfield coordinates of the filled blocks of the active piece, in its current
+    /// rotation and position. See [`ActivePiece::occupied_cells()`].
+    ///
+    /// This is a `Vec`, since custom pieces may have more or fewer than 4 blocks.
+    pub fn active_piece_cells(&self) -> Vec<Vec2I8> {
+        self.active_piece.occupied_cells().collect()
+    }
+
+    /// Gets the queue of upcoming pieces.
+    pub fn next_pieces(&self) -> &VecDeque<PieceData> {
+        &self.next_pieces
+    }
+
+    /// Gets the kinds of the next `n` upcoming pieces, starting with the one that spawns next.
+    ///
+    /// Only [`Game::preview_count()`] pieces are known in advance, so fewer kinds are returned if `n` is larger than that.
+    pub fn next_piece_kinds(&self, n: usize) -> Vec<PieceKind> {
+        self.next_pieces.iter().take(n).map(PieceData::kind).collect()
+    }
+
+    /// Gets the held piece that would be swapped in next, if the hold is full.
+    pub fn held_piece(&self) -> Option<&PieceData> {
+        self.held_pieces.first()
+    }
+
+    /// Gets all held pieces, starting with the one that was held the longest.
+    pub fn held_pieces(&self) -> &[PieceData] {
+        &self.held_pieces
+    }
+
+    /// Gets how many pieces can be held at once.
+    pub fn hold_capacity(&self) -> usize {
+        self.hold_capacity
+    }
+
+    /// Sets how many pieces can be held at once. The default is 1.
+    ///
+    /// Panics if `capacity` is zero. If more pieces are held than fit, the most recently held ones are discarded.
+    pub fn set_hold_capacity(&mut self, capacity: usize) {
+        assert!(capacity > 0, "hold capacity must be at least 1");
+        self.hold_capacity = capacity;
+        self.held_pieces.truncate(capacity);
+    }
+
+    /// Gets how many upcoming pieces are known in advance.
+    pub fn preview_count(&self) -> usize {
+        self.preview_count
+    }
+
+    /// Sets how many upcoming pieces are known in advance. The default is [`DEFAULT_PREVIEW_COUNT`].
+    ///
+    /// Panics if `count` is zero. The queue is topped up from the randomizer right away.
+    /// If it holds more pieces than the new count, the last ones are discarded.
+    pub fn set_preview_count(&mut self, count: usize) {
+        assert!(count > 0, "preview count must be at least 1");
+        self.preview_count = count;
+        self.next_pieces.truncate(count);
+        self.fill_next_pieces();
+    }
+
+    /// Determines whether new pieces are moved down one row into the visible playfield right after spawning.
+    pub fn spawn_row_nudge(&self) -> bool {
+        self.spawn_row_nudge
+    }
+
+    /// Sets whether new pieces are moved down one row into the visible playfield right after spawning.
+    /// This is on by default. When off, pieces stay in the row they spawned in, just above the visible playfield.
+    ///
+    /// Block outs are detected at the spawn position either way. The nudge only happens if the spawn
+    /// position is free and is skipped if the row below is blocked, so it never causes a block out.
+    pub fn set_spawn_row_nudge(&mut self, enabled: bool) {
+        self.spawn_row_nudge = enabled;
+    }
+
+    /// Gets how many tiles along each axis a block covers when displayed.
+    pub fn scale_factor(&self) -> usize {
+        self.scale_factor
+    }
+
+    /// Restarts the game with [`Game::reset()`], with every block covering `factor` tiles along each axis
+    /// when displayed, such as 2 for "big mode". The default is 1.
+    ///
+    /// The displayed size of the playfield is kept, while the game itself runs on a playfield that is
+    /// `factor` times smaller. That way, pieces move, collide, and clear lines in whole blocks.
+    /// Since the playfield is replaced, the game in progress is lost, so call this before starting to play.
+    /// Use [`Game::display_playfield()`] to get the playfield at its displayed size.
+    ///
+    /// Panics if `factor` is zero, does not evenly divide the displayed size, or makes the playfield too small.
+    pub fn restart_with_scale_factor(&mut self, factor: usize) {
+        assert!(factor > 0, "scale factor must be at least 1");
+
+        let width = self.playfield.width() * self.scale_factor;
+        let height = self.playfield.height() * self.scale_factor;
+        let config = PlayfieldConfig { width: width / factor, height: height / factor };
+        assert!(config.width * factor == width && config.height * factor == height, "scale factor must evenly divide the playfield size");
+
+        let tracks_cell_ages = self.playfield.tracks_cell_ages();
+        self.playfield = Playfield::with_size(config);
+        self.playfield.set_tracks_cell_ages(tracks_cell_ages);
+        self.scale_factor = factor;
+        self.reset();
+    }
+
+    /// Gets the playfield as displayed, with every block covering [`Game::scale_factor()`] tiles along each axis.
+    pub fn display_playfield(&self) -> Playfield {
+        self.playfield.upscaled(self.scale_factor)
+    }
+
+    /// Sets whether the playfield records when tiles were locked, so their age can be queried
+    /// with [`Game::cell_age()`]. This is off by default.
+    pub fn set_tracks_cell_ages(&mut self, enabled: bool) {
+        self.playfield.set_tracks_cell_ages(enabled);
+    }
+
+    /// Gets how many seconds of play ago a tile was locked, such as to let it fade out in an invisible mode.
+    /// This is measured with [`Game::elapsed_seconds()`], so time spent paused does not count.
+    ///
+    /// Returns [`None`] if ages are not tracked or the tile was not set by locking a piece.
+    pub fn cell_age(&self, x: usize, y: usize) -> Option<f64> {
+        self.playfield.cell_age(x, y, self.elapsed_seconds)
+    }
+
+    /// Gets how high the stack is, measured by its tallest column. An empty playfield has a height of zero.
+    pub fn stack_height(&self) -> usize {
+        self.playfield.max_height()
+    }
+
+    /// Determines whether the stack is at least `threshold` rows high, such as to warn the player.
+    pub fn is_in_danger(&self, threshold: usize) -> bool {
+        self.stack_height() >= threshold
+    }
+
+    /// Gets the stack height at which [`GameEvent::DangerChanged`] reports danger, if any.
+    pub fn danger_threshold(&self) -> Option<usize> {
+        self.danger_threshold
+    }
+
+    /// Sets the stack height at which [`GameEvent::DangerChanged`] reports danger. There is none by default.
+    ///
+    /// The danger is only over once the stack falls [`DANGER_HYSTERESIS`] rows below the threshold,
+    /// so a stack hovering around it doesn't flip back and forth.
+    pub fn set_danger_threshold(&mut self, rows: usize) {
+        self.danger_threshold = Some(rows);
+        self.update_danger();
+    }
+
+    /// Stops reporting danger, ending it if the stack was in danger.
+    pub fn clear_danger_threshold(&mut self) {
+        self.danger_threshold = None;
+        self.update_danger();
+    }
+
+    /// Determines whether the stack is currently in danger, as last reported by [`GameEvent::DangerChanged`].
+    pub fn is_danger_active(&self) -> bool {
+        self.in_danger
+    }
+
+    /// Gets the palette the pieces are displayed in.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Sets the palette the pieces are displayed in.
+    ///
+    /// This only changes how pieces are displayed; the game itself keeps track of kinds rather than colors.
+    /// Renderers look up colors with [`Game::piece_color()`] and [`Playfield::tile_color()`].
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Gets the color a piece is displayed in with the current palette.
+    pub fn piece_color(&self, piece: &PieceData) -> Color {
+        self.palette.color(piece.kind())
+    }
+
+    /// Gets the total score.
+    pub fn score(&self) -> u64 {
+        self.score.points()
+    }
+
+    /// Gets the current level.
+    pub fn level(&self) -> u32 {
+        self.score.level()
+    }
+
+    /// Gets the total amount of cleared lines.
+    pub fn lines_cleared(&self) -> usize {
+        self.score.lines()
+    }
+
+    /// Gets the current combo, or `-1` if the last locked piece did not clear any lines.
+    pub fn combo(&self) -> i32 {
+        self.score.combo()
+    }
+
+    /// Gets whether the next tetris or T-spin would be back-to-back.
+    pub fn back_to_back(&self) -> bool {
+        self.score.back_to_back()
+    }
+
+    /// Gets how many pieces of each kind have been spawned, indexed by [`PieceKind::index()`].
+    /// Pieces swapped back in from the hold are counted again.
+    pub fn piece_counts(&self) -> [u32; PIECE_COUNT] {
+        self.piece_counts
+    }
+
+    /// Gets how many pieces have been locked into the playfield.
+    pub fn pieces_placed(&self) -> u32 {
+        self.pieces_placed
+    }
+
+    /// Gets the play time in seconds, as accumulated by [`Game::tick()`] until the game is over.
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed_seconds
+    }
+
+    /// Gets the average amount of pieces placed per second of play time.
+    /// This is zero if no time has passed yet.
+    pub fn pieces_per_second(&self) -> f64 {
+        if self.elapsed_seconds > 0.0 {
+            self.pieces_placed as f64 / self.elapsed_seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// Fills the queue of upcoming pieces and spawns the first piece, which was drawn before them.
+    fn spawn_first_piece(&mut self, first: PieceData) {
+        self.fill_next_pieces();
+        self.spawn_new_piece(first);
+    }
+
+    /// Checks the stack height against the danger threshold and reports when the danger begins or ends.
+    fn update_danger(&mut self) {
+        let in_danger = match self.danger_threshold {
+            Some(threshold) => {
+                let height = self.stack_height();
+                if self.in_danger {
+                    height + DANGER_HYSTERESIS >= threshold
+                } else {
+                    height >= threshold
+                }
+            }
+            None => false
+        };
+
+        if in_danger != self.in_danger {
+            self.in_danger = in_danger;
+            self.emit(GameEvent::DangerChanged { in_danger });
+        }
+    }
+
+    /// Tops up the queue of upcoming pieces to the preview count.
+    fn fill_next_pieces(&mut self) {
+        while self.next_pieces.len() < self.preview_count {
+            let piece = self.draw_piece();
+            self.next_pieces.push_back(piece);
+        }
+    }
+
+    /// Draws the next piece from the randomizer, counting the draw for [`Game::restore()`].
+    fn draw_piece(&mut self) -> PieceData {
+        self.pieces_drawn += 1;
+        self.randomizer.next_piece(&self.pieces)
+    }
+
+    /// Pops the next piece of the upcoming pieces.
+    fn pop_next_piece(&mut self) -> PieceData {
+        let next_piece = self.next_pieces.pop_front().expect("next_pieces queue cannot be empty");
+        self.fill_next_pieces();
+        next_piece
+    }
+
+    /// Spawns the next piece from the queue, ending the game if it is blocked out.
+    ///
+    /// Returns whether it spawned.
+    fn spawn_next_piece(&mut self) -> bool {
+        let next_piece = self.pop_next_piece();
+        let spawned = self.spawn_new_piece(next_piece);
+        if !spawned {
+            self.top_out(GameOverReason::BlockOut);
+        }
+
+        spawned
+    }
+
+    /// Spawns the next piece once the entry delay is over and applies the inputs buffered during it.
+    fn finish_entry_delay(&mut self) {
+        let mut buffered = core::mem::take(&mut self.input_buffer);
+        if !self.spawn_next_piece() {
+            return;
+        }
+
+        // Holding swaps out the piece, so it has to come before anything applied to that piece
+        if let Some(index) = buffered.iter().position(|&input| input == GameInput::Hold) {
+            buffered.remove(index);
+            self.hold_piece();
+        }
+
+        // A fully charged direction moves the new piece straight away
+        if !self.is_game_over() {
+            self.apply_auto_shift(0.0);
+        }
+
+        for input in buffered {
+            if self.is_game_over() || self.is_in_are() {
+                break;
+            }
+
+            self.handle_input(input);
+        }
+    }
+
+    /// Determines whether the player cannot control the active piece right now.
+    fn controls_locked(&self) -> bool {
+        self.is_paused || self.are_timer.is_some() || self.is_game_over()
+    }
+
+    /// Determines what kind of T-spin, if any, locking the active piece in place would be.
+    ///
+    /// This uses the 3-corner rule: the T-piece must have been rotated into place and
+    /// at least 3 of the corners of its 3x3 box must be blocked.
+    fn detect_t_spin(&self) -> ClearKind {
+        let piece = &self.active_piece;
+        if !self.last_move_was_rotation || piece.kind() != PieceKind::T {
+            return ClearKind::Normal;
+        }
+
+        let corners = self.playfield.corner_occupancy(piece);
+        if corners.iter().filter(|&&c| c).count() < 3 {
+            return ClearKind::Normal;
+        }
+
+        let front = T_FRONT_CORNERS[piece.rotation];
+        if corners[front[0]] && corners[front[1]] {
+            ClearKind::TSpin
+        } else {
+            ClearKind::TSpinMini
+        }
+    }
+
+    /// Spawns a new active piece onto the field, replacing the old one.
+    ///
+    /// Returns `false` if the piece is blocked out, that is it overlaps the stack at its spawn position.
+    pub(crate) fn spawn_new_piece(&mut self, new_piece: PieceData) -> bool {
+        self.active_piece = self.create_spawned_piece(new_piece);
+        self.last_move_was_rotation = false;
+        self.lock_timer = self.lock_delay;
+        self.lock_resets = 0;
+        self.lock_pending = false;
+        self.gravity_progress = 0.0;
+        self.emit(GameEvent::PieceSpawned);
+
+        let spawned = !self.playfield.has_overlap(&self.active_piece);
+        if spawned {
+            self.piece_counts[self.active_piece.kind().index()] += 1;
+        }
+
+        spawned
+    }
+
+    /// Creates the active piece as it would be spawned onto the current playfield.
+    ///
+    /// A piece that overlaps at its spawn position is left there, so it is recognized as blocked out.
+    fn create_spawned_piece(&self, new_piece: PieceData) -> ActivePiece {
+        // Spawn one row above the visible playfield, then drop into it if there is room and it's enabled
+        let anchor = Vec2I8::new((self.playfield.width() / 2) as i8, self.playfield.height() as i8);
+        let spawn_pos = anchor + new_piece.spawn_offset() - Vec2I8::new(0, 1);
+
+        let mut piece = ActivePiece::new(new_piece, spawn_pos);
+
+        if self.spawn_row_nudge && !self.playfield.has_overlap(&piece) {
+            piece.position.y += 1;
+            if self.playfield.has_overlap(&piece) {
+                piece.position.y -= 1;
+            }
+        }
+
+        piece
+    }
+
+    /// Moves the active piece one column in a direction. Returns whether it succeeded.
+    fn shift(&mut self, dir: HorizontalDir) -> bool {
+        match dir {
+            HorizontalDir::Left => self.move_left(),
+            HorizontalDir::Right => self.move_right()
+        }
+    }
+
+    /// Repeats the moves of the held direction that are due after `dt` seconds.
+    fn apply_auto_shift(&mut self, dt: f64) {
+        let mut shift = match self.auto_shift {
+            Some(shift) => shift,
+            None => return
+        };
+
+        shift.delay -= dt;
+        while shift.delay <= 0.0 {
+            if self.arr <= 0.0 {
+                // Instantly slide to the wall
+                while self.shift(shift.dir) {}
+                shift.delay = 0.0;
+                break;
+            }
+
+            if !self.shift(shift.dir) {
+                // Blocked moves don't build up, but are retried on the next tick
+                shift.delay = 0.0;
+                break;
+            }
+
+            shift.delay += self.arr;
+        }
+
+        self.auto_shift = Some(shift);
+    }
+
+    /// Ends the game because the stack reached the top.
+    fn top_out(&mut self, reason: GameOverReason) {
+        self.game_over_reason = Some(reason);
+        self.emit(GameEvent::GameOver { reason });
+    }
+
+    /// Passes an event to the event handler, if there is one.
+    fn emit(&mut self, event: GameEvent) {
+        if let Some(handler) = &mut self.event_handler {
+            handler(&event);
+        }
+    }
+
+    /// Attempts to rotate the active piece into the target rotation, trying the kick tests if needed.
+    fn try_rotate(&mut self, trg_rot: usize, kick_tests: &[Vec2I8]) -> bool {
+        if self.controls_locked() {
+            return false;
+        }
+
+        match self.playfield.rotated_piece(&self.active_piece, trg_rot, kick_tests) {
+            Some((rotated, kick_used)) => {
+                self.active_piece = rotated;
+                self.last_move_was_rotation = true;
+                self.emit(GameEvent::Rotated { kick_used });
+                self.apply_move_reset(true)
+            }
+            None => false
+        }
+    }
+
+    /// Resets the lock delay after a successful move of a resting piece,
+    /// unless it has been reset too often already.
+    ///
+    /// Returns whether the move succeeded.
+    fn apply_move_reset(&mut self, moved: bool) -> bool {
+        if moved && self.is_resting() && self.lock_resets < MAX_LOCK_RESETS {
+            self.lock_timer = self.lock_delay;
+            self.lock_resets += 1;
+        }
+
+        moved
+    }
+
+    /// Attempts to perform a movement action through the specified function.
+    fn try_move(&mut self, change: impl FnOnce(&mut Vec2I8, &mut usize)) -> bool {
+        if self.controls_locked() {
+            return false;
+        }
+
+        // Keep a backup in case moving fails
+        let old_pos = self.active_piece.position;
+        let old_rot = self.active_piece.rotation;
+
+        // Apply the changes from the fn to the active state
+        change(&mut self.active_piece.position, &mut self.active_piece.rotation);
+
+        // If it overlaps, we need to reset.
+        if self.playfield.has_overlap(&self.active_piece) {
+            self.active_piece.position = old_pos;
+            self.active_piece.rotation = old_rot;
+            false
+        } else {
+            self.last_move_was_rotation = self.active_piece.rotation != old_rot;
+            true
+        }
+    }
+
+    /// Gets where a piece would end up after a single movement or rotation input, including kicks.
+    ///
+    /// Returns [`None`] if the move is blocked or the input does not just move the piece.
+    fn moved_piece(&self, piece: &ActivePiece, input: GameInput) -> Option<ActivePiece> {
+        let (trg_rot, kicks) = match input {
+            GameInput::MoveLeft => return self.shifted_piece(piece, Vec2I8::new(-1, 0)),
+            GameInput::MoveRight => return self.shifted_piece(piece, Vec2I8::new(1, 0)),
+            GameInput::SoftDrop => return self.shifted_piece(piece, Vec2I8::new(0, 1)),
+            GameInput::RotateCW => piece.right_rotation(),
+            GameInput::RotateCCW => piece.left_rotation(),
+            GameInput::Rotate180 => {
+                let trg_rot = (piece.rotation + 2) % 4;
+                return self.playfield.rotated_piece(piece, trg_rot, &ROTATE_180_KICKS).map(|(rotated, _)| rotated);
+            }
+            GameInput::HardDrop | GameInput::Hold => return None
+        };
+
+        self.playfield.rotated_piece(piece, trg_rot, &kicks).map(|(rotated, _)| rotated)
+    }
+
+    /// Gets a piece moved by the given offset, or [`None`] if it would overlap.
+    fn shifted_piece(&self, piece: &ActivePiece, offset: Vec2I8) -> Option<ActivePiece> {
+        let mut moved = piece.clone();
+        moved.position += offset;
+        if self.playfield.has_overlap(&moved) {
+            None
+        } else {
+            Some(moved)
+        }
+    }
+
+    /// Determines whether the active piece could be moved by the given offset.
+    fn can_shift(&self, offset: Vec2I8) -> bool {
+        !self.controls_locked() && self.shifted_piece(&self.active_piece, offset).is_some()
+    }
+
+    /// Determines whether the active piece could be rotated into the target rotation, trying the kick tests if needed.
+    fn can_rotate(&self, trg_rot: usize, kick_tests: &[Vec2I8]) -> bool {
+        !self.controls_locked() && self.playfield.rotated_piece(&self.active_piece, trg_rot, kick_tests).is_some()
+    }
+}
+
+impl Placement {
+    /// Creates the placement describing the current state of a piece.
+    fn of(piece: &ActivePiece) -> Placement {
+        Placement {
+            rotation: piece.rotation,
+            position: piece.position
+        }
+    }
+}
+
+/// Clones the entire state of the game, including the randomizer, so the clone continues with the same pieces.
+///
+/// The event handler is not cloned, so the clone starts without one.
+impl Clone for Game {
+    fn clone(&self) -> Self {
+        Game {
+            playfield: self.playfield.clone(),
+            active_piece: self.active_piece.clone(),
+            next_pieces: self.next_pieces.clone(),
+            held_pieces: self.held_pieces.clone(),
+            hold_capacity: self.hold_capacity,
+            preview_count: self.preview_count,
+            scale_factor: self.scale_factor,
+            spawn_row_nudge: self.spawn_row_nudge,
+            used_hold: self.used_hold,
+            last_move_was_rotation: self.last_move_was_rotation,
+            lock_delay: self.lock_delay,
+            lock_timer: self.lock_timer,
+            lock_resets: self.lock_resets,
+            lock_mode: self.lock_mode,
+            lock_pending: self.lock_pending,
+            are: self.are,
+            are_timer: self.are_timer,
+            input_buffering: self.input_buffering,
+            input_buffer: self.input_buffer.clone(),
+            game_over_reason: self.game_over_reason,
+            danger_threshold: self.danger_threshold,
+            in_danger: self.in_danger,
+            is_paused: self.is_paused,
+            das: self.das,
+            arr: self.arr,
+            auto_shift: self.auto_shift,
+            gravity: self.gravity,
+            gravity_progress: self.gravity_progress,
+            pieces: self.pieces.clone(),
+            palette: self.palette,
+            randomizer: self.randomizer.clone(),
+            initial_randomizer: self.initial_randomizer.clone(),
+            pieces_drawn: self.pieces_drawn,
+            score: self.score.clone(),
+            piece_counts: self.piece_counts,
+            pieces_placed: self.pieces_placed,
+            elapsed_seconds: self.elapsed_seconds,
+            event_handler: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+impl GameSnapshot {
+    /// Gets the captured playfield.
+    pub fn playfield(&self) -> &Playfield {
+        &self.playfield
+    }
+
+    /// Gets the captured active piece.
+    pub fn active_piece(&self) -> &ActivePiece {
+        &self.active_piece
+    }
+
+    /// Gets the captured queue of upcoming pieces.
+    pub fn next_pieces(&self) -> &VecDeque<PieceData> {
+        &self.next_pieces
+    }
+
+    /// Gets the captured held piece that would be swapped in next.
+    pub fn held_piece(&self) -> Option<&PieceData> {
+        self.held_pieces.first()
+    }
+
+    /// Gets all captured held pieces, starting with the one that was held the longest.
+    pub fn held_pieces(&self) -> &[PieceData] {
+        &self.held_pieces
+    }
+}
+
+impl ActivePiece {
+    /// Creates a new active piece with the given piece data and a spawn position.
+    pub fn new(piece_data: PieceData, spawn_pos: Vec2I8) -> ActivePiece {
+        ActivePiece {
+            piece_data,
+            position: spawn_pos,
+            rotation: 0
+        }
+    }
+
+    /// Gets which piece this is.
+    pub fn kind(&self) -> PieceKind {
+        self.piece_data.kind()
+    }
+
+    /// Gets the named rotation state of this piece.
+    ///
+    /// Panics if the `rotation` field is not in [0..=3].
+    pub fn orientation(&self) -> Orientation {
+        Orientation::from_index(self.rotation).expect("rotation index out of range")
+    }
+
+    /// Gets the matrix that is currently in use based on its rotation.
+    pub fn matrix(&self) -> PieceBoolMatrix {
+        self.piece_data.state(self.rotation).matrix()
+    }
+
+    /// Gets the matrix for any rotation of this piece without changing its current rotation.
+    ///
+    /// Panics if `rotation` is not in [0..=3].
+    pub fn matrix_at(&self, rotation: usize) -> PieceBoolMatrix {
+        assert!(rotation < 4, "rotation index out of range: {}", rotation);
+        self.piece_data.state(rotation).matrix()
+    }
+
+    /// Gets the target rotation and the kick tests for rotating left.
+    fn left_rotation(&self) -> (usize, [Vec2I8; 4]) {
+        let trg_rot = if self.rotation == 0 { 3 } else { self.rotation - 1 };
+        (trg_rot, neg_kicks(self.piece_data.state(trg_rot).kick_tests()))
+    }
+
+    /// Gets the target rotation and the kick tests for rotating right.
+    fn right_rotation(&self) -> (usize, [Vec2I8; 4]) {
+        let trg_rot = if self.rotation == 3 { 0 } else { self.rotation + 1 };
+        (trg_rot, *self.piece_data.state(self.rotation).kick_tests())
+    }
+
+    /// Gets the playfield coordinates of the filled blocks.
+    /// The coordinates may be negative or out of range if the piece is not fully on the playfield.
+    pub fn occupied_cells(&self) -> impl Iterator<Item = Vec2I8> {
+        let mat = self.matrix();
+        let position = self.position;
+
+        // mat dimensions are 4x4
+        (0..4usize)
+            .flat_map(|x| (0..4usize).map(move |y| (x, y)))
+            .filter(move |&(x, y)| mat[x][y])
+            .map(move |(x, y)| position + Vec2I8::new(x as i8, y as i8))
+    }
+
+    /// Gets the inclusive minimum and maximum playfield coordinates of the filled blocks.
+    /// Empty rows and columns of the matrix are not included.
+    pub fn bounding_box(&self) -> (Vec2I8, Vec2I8) {
+        let (min, max) = self.piece_data.state(self.rotation).bounds();
+        (self.position + min, self.position + max)
+    }
+}
+
+impl PlayfieldConfig {
+    /// The largest supported visible height, so the true height still fits piece coordinates.
+    pub const MAX_HEIGHT: usize = 63;
+
+    /// The largest supported width, so it still fits piece coordinates.
+    pub const MAX_WIDTH: usize = 127;
+
+    /// Gets the guideline dimensions of 10 by 20 visible blocks.
+    pub const fn guideline() -> PlayfieldConfig {
+        PlayfieldConfig { width: PLAYFIELD_WIDTH, height: PLAYFIELD_HEIGHT }
+    }
+
+    /// Gets the "true" height including the non-visible parts.
+    pub const fn true_height(&self) -> usize {
+        self.height * 2
+    }
+}
+
+impl HoldOutcome {
+    /// Gets the kind of the piece that is active after holding, regardless of whether it was swapped in
+    /// or taken from the queue. Returns [`None`] if holding was blocked.
+    pub fn active_kind(self) -> Option<PieceKind> {
+        match self {
+            HoldOutcome::Swapped(kind) | HoldOutcome::FilledFromQueue(kind) => Some(kind),
+            HoldOutcome::Blocked => None
+        }
+    }
+}
+
+impl fmt::Display for PlayfieldParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayfieldParseError::WidthMismatch { row, expected, found } =>
+                write!(f, "row {} has {} tiles, but the playfield is {} wide", row, found, expected),
+            PlayfieldParseError::TooManyRows { expected, found } =>
+                write!(f, "found {} rows, but the playfield has at most {}", found, expected),
+            PlayfieldParseError::InvalidChar { row, column, found } =>
+                write!(f, "invalid tile {:?} at row {}, column {}", found, row, column)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PlayfieldParseError {}
+
+impl Default for PlayfieldConfig {
+    fn default() -> Self {
+        PlayfieldConfig::guideline()
+    }
+}
+
+impl Playfield {
+    /// Creates a new empty playfield with the guideline dimensions. This means all its tiles are black.
+    pub fn new() -> Playfield {
+        Playfield::with_size(PlayfieldConfig::guideline())
+    }
+
+    /// Creates a new empty playfield with the given dimensions.
+    ///
+    /// Panics if the playfield is too narrow to fit every piece or its dimensions exceed
+    /// [`PlayfieldConfig::MAX_WIDTH`] or [`PlayfieldConfig::MAX_HEIGHT`].
+    pub fn with_size(config: PlayfieldConfig) -> Playfield {
+        assert!((4..=PlayfieldConfig::MAX_WIDTH).contains(&config.width), "Invalid playfield width");
+        assert!((1..=PlayfieldConfig::MAX_HEIGHT).contains(&config.height), "Invalid playfield height");
+
+        Playfield {
+            width: config.width,
+            height: config.height,
+            fill_state: vec![Color::BLACK; config.width * config.true_height()],
+            piece_kinds: vec![None; config.width * config.true_height()],
+            lock_times: None,
+        }
+    }
+
+    /// Gets the dimensions of this playfield.
+    pub fn config(&self) -> PlayfieldConfig {
+        PlayfieldConfig { width: self.width, height: self.height }
+    }
+
+    /// Gets the width of the playfield in blocks.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the visible height of the playfield in blocks.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the "true" height of the playfield in blocks including the non-visible parts.
+    pub fn true_height(&self) -> usize {
+        self.height * 2
+    }
+
+    /// Determines whether the filled playfield tiles overlap with the active piece.
+    pub fn has_overlap(&self, piece: &ActivePiece) -> bool {
+        piece.occupied_cells().any(|c| self.has_tile_signed(c.x.into(), c.y.into()))
+    }
+
+    /// Determines what a piece collides with, if anything.
+    ///
+    /// If it both overlaps set tiles and is out of range, this is [`CollisionKind::Block`].
+    pub fn collision_kind(&self, piece: &ActivePiece) -> Option<CollisionKind> {
+        let mut collision = None;
+        for c in piece.occupied_cells() {
+            match self.signed_index(c.x.into(), c.y.into()) {
+                Some(index) if !self.fill_state[index].is_black() => return Some(CollisionKind::Block),
+                Some(_) => {}
+                None => collision = Some(CollisionKind::Wall)
+            }
+        }
+
+        collision
+    }
+
+    /// Gets how many rows a piece can fall before it comes to rest.
+    pub fn drop_distance(&self, piece: &ActivePiece) -> usize {
+        let mut piece = piece.clone();
+        let mut distance = 0;
+
+        loop {
+            piece.position.y += 1;
+            if self.has_overlap(&piece) {
+                return distance;
+            }
+
+            distance += 1;
+        }
+    }
+
+    /// Rotates a piece into the target rotation, trying the kick tests in order if it overlaps.
+    ///
+    /// Returns the rotated piece and whether a kick test was needed, or [`None`] if all attempts overlap.
+    fn rotated_piece(&self, piece: &ActivePiece, trg_rot: usize, kick_tests: &[Vec2I8]) -> Option<(ActivePiece, bool)> {
+        let mut rotated = piece.clone();
+        rotated.rotation = trg_rot;
+        if !self.has_overlap(&rotated) {
+            return Some((rotated, false));
+        }
+
+        for &t in kick_tests.iter() {
+            rotated.position = piece.position + t;
+            if !self.has_overlap(&rotated) {
+                return Some((rotated, true));
+            }
+        }
+
+        None
+    }
+
+    /// Determines which corners of the 3x3 box of a piece are blocked, either by tiles or by being out of range.
+    ///
+    /// The corners are ordered top-left, top-right, bottom-left, bottom-right.
+    pub fn corner_occupancy(&self, piece: &ActivePiece) -> [bool; 4] {
+        let x_base = i32::from(piece.position.x);
+        let y_base = i32::from(piece.position.y);
+
+        let corner = |x: i32, y: i32| self.has_tile_signed(x_base + x, y_base + y);
+        [corner(0, 0), corner(2, 0), corner(0, 2), corner(2, 2)]
+    }
+
+    /// Gets the color of a tile. If not in range, it is [`Color::WHITE`].
+    pub fn get_tile(&self, x: usize, y: usize) -> Color {
+        if self.is_in_bounds(x, y) {
+            self.fill_state[y * self.width + x]
+        } else {
+            Color::WHITE
+        }
+    }
+
+    /// Gets the color of a tile at signed coordinates. If not in range, including negative coordinates,
+    /// it is [`Color::WHITE`].
+    pub fn get_tile_signed(&self, x: i32, y: i32) -> Color {
+        match self.signed_index(x, y) {
+            Some(index) => self.fill_state[index],
+            None => Color::WHITE
+        }
+    }
+
+    /// Determines if a tile at signed coordinates is set. Tiles out of range, including negative coordinates,
+    /// count as set, so they act as walls.
+    pub fn has_tile_signed(&self, x: i32, y: i32) -> bool {
+        !self.get_tile_signed(x, y).is_black()
+    }
+
+    /// Gets the index into the tiles for signed coordinates, or [`None`] if they are out of range.
+    fn signed_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        let (x, y) = (x as usize, y as usize);
+        if self.is_in_bounds(x, y) {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Gets a mutable reference to the color of a tile.
+    ///
+    /// Like with [`Playfield::set_tile()`], the tile no longer counts as part of a locked piece
+    /// and loses its lock time, since whatever is written to it did not come from locking a piece.
+    pub fn get_tile_mut(&mut self, x: usize, y: usize) -> Option<&mut Color> {
+        if !self.is_in_bounds(x, y) {
+            return None;
+        }
+
+        let index = y * self.width + x;
+        self.piece_kinds[index] = None;
+        if let Some(lock_times) = &mut self.lock_times {
+            lock_times[index] = None;
+        }
+
+        Some(&mut self.fill_state[index])
+    }
+
+    /// Gets what a tile is filled with. Tiles out of range are [`CellKind::Empty`].
+    ///
+    /// Set tiles that were not copied in from a piece, such as garbage, are [`CellKind::Garbage`].
+    pub fn cell_kind(&self, x: usize, y: usize) -> CellKind {
+        if !self.is_in_bounds(x, y) || !self.has_tile(x, y) {
+            return CellKind::Empty;
+        }
+
+        match self.piece_kinds[y * self.width + x] {
+            Some(kind) => CellKind::Piece(kind),
+            None => CellKind::Garbage
+        }
+    }
+
+    /// Gets the color a tile is displayed in with a palette.
+    ///
+    /// Tiles of locked pieces take the color of their kind from the palette. Garbage keeps its own color,
+    /// and empty tiles or tiles out of range are black.
+    pub fn tile_color(&self, x: usize, y: usize, palette: &Palette) -> Color {
+        match self.cell_kind(x, y) {
+            CellKind::Piece(kind) => palette.color(kind),
+            CellKind::Garbage => self.fill_state[y * self.width + x],
+            CellKind::Empty => Color::BLACK
+        }
+    }
+
+    /// Counts the set tiles in the given rows that are [`CellKind::Garbage`]. Rows out of range are ignored.
+    pub fn count_garbage_cells(&self, rows: &[usize]) -> usize {
+        rows.iter()
+            .filter(|&&y| y < self.true_height())
+            .flat_map(|&y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.cell_kind(x, y) == CellKind::Garbage)
+            .count()
+    }
+
+    /// Sets the color of a tile. Tiles out of range are left alone.
+    ///
+    /// Returns whether the tile was in range.
+    pub fn set_tile(&mut self, x: usize, y: usize, color: Color) -> bool {
+        match self.get_tile_mut(x, y) {
+            Some(tile) => {
+                *tile = color;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Determines if a tile is set. That is to say, it is not black and in range.
+    pub fn has_tile(&self, x: usize, y: usize) -> bool {
+        !self.get_tile(x, y).is_black()
+    }
+
+    /// Gets the colors of a row. Panics if the row is not in range.
+    pub fn row(&self, y: usize) -> &[Color] {
+        assert!(y < self.true_height(), "row {} is out of range", y);
+        &self.fill_state[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Iterates over every tile as `(x, y, color)`, including the empty ones and the non-visible rows.
+    ///
+    /// The tiles are visited row by row, starting with the top row, and from left to right within a row.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, Color)> + '_ {
+        let width = self.width;
+        self.fill_state.iter()
+            .enumerate()
+            .map(move |(i, &color)| (i % width, i / width, color))
+    }
+
+    /// Iterates over the set tiles as `(x, y, color)`, in the same order as [`Playfield::iter()`].
+    pub fn filled_cells(&self) -> impl Iterator<Item = (usize, usize, Color)> + '_ {
+        self.iter().filter(|(_, _, color)| !color.is_black())
+    }
+
+    /// Determines whether every tile of a row is set. Panics if the row is not in range.
+    pub fn is_row_full(&self, y: usize) -> bool {
+        self.row(y).iter().all(|c| !c.is_black())
+    }
+
+    /// Determines whether no tile of a row is set. Panics if the row is not in range.
+    pub fn is_row_empty(&self, y: usize) -> bool {
+        self.row(y).iter().all(|c| c.is_black())
+    }
+
+    /// Creates a copy of this playfield where every tile covers `factor` tiles along each axis.
+    ///
+    /// Panics if `factor` is zero or the result exceeds [`PlayfieldConfig::MAX_WIDTH`] or [`PlayfieldConfig::MAX_HEIGHT`].
+    pub fn upscaled(&self, factor: usize) -> Playfield {
+        assert!(factor > 0, "scale factor must be at least 1");
+
+        let mut scaled = Playfield::with_size(PlayfieldConfig { width: self.width * factor, height: self.height * factor });
+        let source_index = |index: usize| {
+            let x = index % scaled.width / factor;
+            let y = index / scaled.width / factor;
+            y * self.width + x
+        };
+
+        let fill_state = (0..scaled.fill_state.len()).map(|i| self.fill_state[source_index(i)]).collect();
+        let lock_times = self.lock_times.as_ref()
+            .map(|lock_times| (0..scaled.fill_state.len()).map(|i| lock_times[source_index(i)]).collect());
+
+        scaled.piece_kinds = (0..scaled.fill_state.len()).map(|i| self.piece_kinds[source_index(i)]).collect();
+        scaled.fill_state = fill_state;
+        scaled.lock_times = lock_times;
+        scaled
+    }
+
+    /// Flips the playfield left-to-right in place.
+    pub fn mirror_horizontal(&mut self) {
+        for row in self.fill_state.chunks_exact_mut(self.width) {
+            row.reverse();
+        }
+
+        for row in self.piece_kinds.chunks_exact_mut(self.width) {
+            row.reverse();
+        }
+
+        if let Some(lock_times) = &mut self.lock_times {
+            for row in lock_times.chunks_exact_mut(self.width) {
+                row.reverse();
+            }
+        }
+    }
+
+    /// Empties every tile of the playfield.
+    pub fn clear(&mut self) {
+        self.fill_state.fill(Color::BLACK);
+        self.piece_kinds.fill(None);
+        if let Some(lock_times) = &mut self.lock_times {
+            lock_times.fill(None);
+        }
+    }
+
+    /// Determines whether the same tiles are set in both playfields, regardless of their colors.
+    /// Playfields with different dimensions never match.
+    pub fn occupancy_eq(&self, other: &Playfield) -> bool {
+        self.config() == other.config() && self.fill_state.iter()
+            .zip(other.fill_state.iter())
+            .all(|(a, b)| a.is_black() == b.is_black())
+    }
+
+    /// Gets which visible tiles are set as one bitmask per row, starting at the top visible row.
+    /// Bit `x` of a row is set if the tile in column `x` is set.
+    ///
+    /// Panics if the playfield is wider than [`MAX_BITBOARD_WIDTH`].
+    pub fn to_bitboard(&self) -> Vec<u16> {
+        assert!(self.width <= MAX_BITBOARD_WIDTH, "playfield is too wide for a bitboard");
+
+        self.fill_state[self.height * self.width..]
+            .chunks_exact(self.width)
+            .map(|row| row.iter()
+                .enumerate()
+                .fold(0, |bits, (x, c)| bits | (u16::from(!c.is_black()) << x)))
+            .collect()
+    }
+
+    /// Creates a playfield of the given width from one bitmask per visible row, as returned by
+    /// [`Playfield::to_bitboard()`]. Set tiles are filled with [`GARBAGE_COLOR`] and bits past the width are ignored.
+    ///
+    /// The visible height is the amount of rows. Panics if the dimensions are not valid for a playfield
+    /// or the width exceeds [`MAX_BITBOARD_WIDTH`].
+    pub fn from_bitboard(width: usize, rows: &[u16]) -> Playfield {
+        assert!(width <= MAX_BITBOARD_WIDTH, "playfield is too wide for a bitboard");
+
+        let mut playfield = Playfield::with_size(PlayfieldConfig { width, height: rows.len() });
+        let start = playfield.height * width;
+        for (row, &bits) in playfield.fill_state[start..].chunks_exact_mut(width).zip(rows) {
+            for (x, tile) in row.iter_mut().enumerate() {
+                if bits & (1 << x) != 0 {
+                    *tile = GARBAGE_COLOR;
+                }
+            }
+        }
+
+        playfield
+    }
+
+    /// Gets a stable hash of which visible tiles are set, regardless of their colors.
+    ///
+    /// This uses 64-bit FNV-1a over the dimensions and the occupancy, so it is the same on every platform
+    /// and across runs, such as for transposition tables or comparing against stored results.
+    pub fn content_hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mix = |hash: u64, byte: u8| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+
+        let mut hash = FNV_OFFSET_BASIS;
+        hash = mix(hash, self.width as u8);
+        hash = mix(hash, self.height as u8);
+
+        // Pack 8 tiles into every byte
+        for chunk in self.fill_state[self.height * self.width..].chunks(8) {
+            let byte = chunk.iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, c)| byte | (u8::from(!c.is_black()) << i));
+            hash = mix(hash, byte);
+        }
+
+        hash
+    }
+
+    /// Determines if no tile of the playfield is set.
+    pub fn is_empty(&self) -> bool {
+        self.fill_state.iter().all(Color::is_black)
+    }
+
+    /// Gets the height of every column, measured from the bottom up to its highest set tile.
+    /// Empty columns have a height of zero.
+    pub fn height_profile(&self) -> Vec<usize> {
+        let true_height = self.true_height();
+        (0..self.width)
+            .map(|x| match (0..true_height).find(|&y| self.has_tile(x, y)) {
+                Some(y) => true_height - y,
+                None => 0,
+            })
+            .collect()
+    }
+
+    /// Gets the height of the tallest column. An empty playfield has a height of zero.
+    pub fn max_height(&self) -> usize {
+        self.height_profile().into_iter().max().unwrap_or(0)
+    }
+
+    /// Gets the sum of the heights of all columns.
+    pub fn aggregate_height(&self) -> usize {
+        self.height_profile().iter().sum()
+    }
+
+    /// Gets the sum of the absolute height differences between neighboring columns.
+    pub fn bumpiness(&self) -> usize {
+        self.height_profile()
+            .windows(2)
+            .map(|w| w[0].abs_diff(w[1]))
+            .sum()
+    }
+
+    /// Gets the amount of empty tiles that have a set tile somewhere above them in the same column.
+    pub fn hole_count(&self) -> usize {
+        let true_height = self.true_height();
+        let mut holes = 0;
+        for x in 0..self.width {
+            if let Some(top) = (0..true_height).find(|&y| self.has_tile(x, y)) {
+                holes += (top..true_height).filter(|&y| !self.has_tile(x, y)).count();
+            }
+        }
+
+        holes
+    }
+
+    /// Determines if a specified coordinate is in bounds.
+    pub fn is_in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.true_height()
+    }
+
+    /// Gets the range of rows that are visible.
+    ///
+    /// Rows are counted from the top, so the non-visible buffer is made up of the rows above this range.
+    pub fn visible_rows(&self) -> Range<usize> {
+        self.height..self.true_height()
+    }
+
+    /// Determines if a row is visible, as opposed to being part of the non-visible buffer or out of range.
+    pub fn is_visible(&self, y: usize) -> bool {
+        self.visible_rows().contains(&y)
+    }
+
+    /// Iterates over all visible tiles as `(x, y, color)`, row by row.
+    ///
+    /// Unlike elsewhere, `y` is counted from the top visible row, so it is in the range [0, [`Playfield::height()`]).
+    pub fn visible_tiles(&self) -> impl Iterator<Item = (usize, usize, Color)> + '_ {
+        let width = self.width;
+        self.fill_state[self.height * width..]
+            .iter()
+            .enumerate()
+            .map(move |(i, &c)| (i % width, i / width, c))
+    }
+
+    /// Copies an active piece into the playfield matrix.
+    pub fn copy_in_piece(&mut self, piece: &ActivePiece) {
+        self.copy_in_piece_with_time(piece, None);
+    }
+
+    /// Copies an active piece into the playfield matrix, recording `time` as the lock time of its tiles
+    /// if [`Playfield::tracks_cell_ages()`].
+    pub fn copy_in_piece_at(&mut self, piece: &ActivePiece, time: f64) {
+        self.copy_in_piece_with_time(piece, Some(time));
+    }
+
+    /// Determines whether the time tiles were locked at is recorded, so [`Playfield::cell_age()`] is available.
+    pub fn tracks_cell_ages(&self) -> bool {
+        self.lock_times.is_some()
+    }
+
+    /// Sets whether the time tiles were locked at is recorded. This is off by default.
+    ///
+    /// Tiles that are already set when turning this on have no known age.
+    pub fn set_tracks_cell_ages(&mut self, enabled: bool) {
+        if !enabled {
+            self.lock_times = None;
+        } else if self.lock_times.is_none() {
+            self.lock_times = Some(vec![None; self.fill_state.len()]);
+        }
+    }
+
+    /// Gets how many seconds before `now` a tile was locked, such as to fade it out over time.
+    ///
+    /// Returns [`None`] if ages are not tracked, the tile is empty or out of range, or it was not set
+    /// by locking a piece with [`Playfield::copy_in_piece_at()`].
+    pub fn cell_age(&self, x: usize, y: usize, now: f64) -> Option<f64> {
+        let lock_times = self.lock_times.as_ref()?;
+        if !self.is_in_bounds(x, y) || !self.has_tile(x, y) {
+            return None;
+        }
+
+        lock_times[y * self.width + x].map(|time| now - time)
+    }
+
+    /// Copies an active piece into the playfield matrix, along with its lock time if tracked.
+    fn copy_in_piece_with_time(&mut self, piece: &ActivePiece, time: Option<f64>) {
+        let color = piece.piece_data.color();
+        for c in piece.occupied_cells() {
+            // Blocks out of range are skipped
+            if let Some(index) = self.signed_index(c.x.into(), c.y.into()) {
+                self.fill_state[index] = color;
+                self.piece_kinds[index] = Some(piece.kind());
+                if let Some(lock_times) = &mut self.lock_times {
+                    lock_times[index] = time;
+                }
+            }
+        }
+    }
+
+    /// Finds all completed lines without removing them, returning their row indices in ascending order.
+    pub fn find_completed_lines(&self) -> Vec<usize> {
+        (0..self.true_height())
+            .filter(|&y| self.is_row_full(y))
+            .collect()
+    }
+
+    /// Removes the rows with the given indices, moving the rows above them down to close the gaps.
+    /// Indices out of range are ignored.
+    pub fn remove_lines(&mut self, rows: &[usize]) {
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+
+        // Move UP in index, so rows removed earlier don't shift the ones still to be removed
+        let width = self.width;
+        let true_height = self.true_height();
+        for &y in rows.iter().filter(|&&y| y < true_height) {
+            self.fill_state.copy_within(..y * width, width);
+            self.fill_state[..width].fill(Color::BLACK);
+            self.piece_kinds.copy_within(..y * width, width);
+            self.piece_kinds[..width].fill(None);
+
+            if let Some(lock_times) = &mut self.lock_times {
+                lock_times.copy_within(..y * width, width);
+                lock_times[..width].fill(None);
+            }
+        }
+    }
+
+    /// Shifts all rows up by `count` and fills the new bottom rows with garbage, leaving `hole_column` empty.
+    /// Rows pushed above the top of the playfield are discarded.
+    ///
+    /// A `hole_column` past the right wall is a bug in the caller. Debug builds panic on it,
+    /// while release builds put the hole into the rightmost column instead.
+    pub fn add_garbage_lines(&mut self, count: usize, hole_column: usize) {
+        debug_assert!(hole_column < self.width, "hole column {} is out of range", hole_column);
+
+        let width = self.width;
+        let hole_column = hole_column.min(width - 1);
+        let count = count.min(self.true_height());
+        self.fill_state.copy_within(count * width.., 0);
+
+        let start = self.fill_state.len() - count * width;
+        for row in self.fill_state[start..].chunks_exact_mut(width) {
+            row.fill(GARBAGE_COLOR);
+            row[hole_column] = Color::BLACK;
+        }
+
+        self.piece_kinds.copy_within(count * width.., 0);
+        self.piece_kinds[start..].fill(None);
+
+        // Garbage was never locked, so it has no age
+        if let Some(lock_times) = &mut self.lock_times {
+            lock_times.copy_within(count * width.., 0);
+            lock_times[start..].fill(None);
+        }
+    }
+
+    /// Clears all completed lines, returning the amount of lines that were cleared.
+    pub fn clear_completed_lines(&mut self) -> usize {
+        let rows = self.find_completed_lines();
+        self.remove_lines(&rows);
+        rows.len()
+    }
+
+    /// Parses a guideline-sized playfield from a grid of `#` for set and `.` for empty tiles, with one line per row.
+    /// See [`Playfield::from_ascii_with_size()`] for details.
+    pub fn from_ascii(text: &str) -> Result<Playfield, PlayfieldParseError> {
+        Playfield::from_ascii_with_size(PlayfieldConfig::guideline(), text)
+    }
+
+    /// Parses a playfield with the given dimensions from a grid of `#` for set and `.` for empty tiles,
+    /// with one line per row. Set tiles are filled with [`GARBAGE_COLOR`].
+    ///
+    /// Surrounding whitespace and empty lines are ignored. The rows are placed at the bottom of the playfield,
+    /// so only the lowest rows need to be written out.
+    pub fn from_ascii_with_size(config: PlayfieldConfig, text: &str) -> Result<Playfield, PlayfieldParseError> {
+        let mut playfield = Playfield::with_size(config);
+        let rows: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        if rows.len() > playfield.true_height() {
+            return Err(PlayfieldParseError::TooManyRows { expected: playfield.true_height(), found: rows.len() });
+        }
+
+        let y_base = playfield.true_height() - rows.len();
+        for (row, line) in rows.iter().enumerate() {
+            let found = line.chars().count();
+            if found != playfield.width {
+                return Err(PlayfieldParseError::WidthMismatch { row, expected: playfield.width, found });
+            }
+
+            for (column, c) in line.chars().enumerate() {
+                match c {
+                    ASCII_FILLED => playfield.fill_state[(y_base + row) * playfield.width + column] = GARBAGE_COLOR,
+                    ASCII_EMPTY => (),
+                    found => return Err(PlayfieldParseError::InvalidChar { row, column, found })
+                }
+            }
+        }
+
+        Ok(playfield)
+    }
+
+    /// Renders the playfield as a grid of `#` for set and `.` for empty tiles, with one line per row.
+    /// The non-visible rows are only included if `include_hidden` is set.
+    pub fn to_ascii(&self, include_hidden: bool) -> String {
+        let first_row = if include_hidden { 0 } else { self.height };
+        (first_row..self.true_height())
+            .map(|y| self.row(y).iter().map(|c| if c.is_black() { ASCII_EMPTY } else { ASCII_FILLED }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Formats the visible rows of the playfield as ASCII, as per [`Playfield::to_ascii()`].
+impl fmt::Display for Playfield {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ascii(false))
+    }
+}
+
+impl PartialEq for Playfield {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.fill_state == other.fill_state
+    }
+}
+
+impl Eq for Playfield {}
+
+impl Hash for Playfield {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.fill_state.hash(state);
+    }
+}
+
+impl Default for Playfield {
+    fn default() -> Self {
+        Playfield::new()
+    }
+}
+
+/// Gets the color of the tile at `(x, y)`.
+///
+/// Panics if the tile is not in range. Use [`Playfield::get_tile()`] to not panic.
+impl Index<(usize, usize)> for Playfield {
+    type Output = Color;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Color {
+        assert!(self.is_in_bounds(x, y), "tile ({}, {}) is out of range", x, y);
+        &self.fill_state[y * self.width + x]
+    }
+}
+
+/// Gets a mutable reference to the color of the tile at `(x, y)`, like [`Playfield::get_tile_mut()`].
+/// The tile no longer counts as part of a locked piece and loses its lock time.
+///
+/// Panics if the tile is not in range.
+impl IndexMut<(usize, usize)> for Playfield {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Color {
+        self.get_tile_mut(x, y).unwrap_or_else(|| panic!("tile ({}, {}) is out of range", x, y))
+    }
+}
+
+/// The serialized form of a [`Playfield`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PlayfieldRows<C, K> {
+    rows: Vec<C>,
+    piece_kinds: Vec<K>
+}
+
+/// Serializes the playfield as its rows of colors and the matching rows of piece kinds,
+/// including the non-visible rows. Lock times are not serialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Playfield {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&PlayfieldRows {
+            rows: self.fill_state.chunks_exact(self.width).collect(),
+            piece_kinds: self.piece_kinds.chunks_exact(self.width).collect()
+        }, serializer)
+    }
+}
+
+/// Deserializes the playfield from its rows of colors and piece kinds, including the non-visible ones.
+/// The dimensions are taken from the amount and length of the rows.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Playfield {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let PlayfieldRows { rows, piece_kinds } = PlayfieldRows::<Vec<Color>, Vec<Option<PieceKind>>>::deserialize(deserializer)?;
+        let width = rows.first().map_or(0, Vec::len);
+        if !(4..=PlayfieldConfig::MAX_WIDTH).contains(&width) || rows.iter().any(|r| r.len() != width) {
+            return Err(D::Error::custom("playfield rows must have the same, valid width"));
+        }
+
+        if rows.len() % 2 != 0 || !(1..=PlayfieldConfig::MAX_HEIGHT).contains(&(rows.len() / 2)) {
+            return Err(D::Error::invalid_length(rows.len(), &"an even amount of rows within the supported height"));
+        }
+
+        if piece_kinds.len() != rows.len() || piece_kinds.iter().any(|r| r.len() != width) {
+            return Err(D::Error::custom("piece kinds must have the same dimensions as the rows"));
+        }
+
+        let fill_state = rows.concat();
+        let piece_kinds = piece_kinds.concat();
+        if fill_state.iter().zip(&piece_kinds).any(|(color, kind)| color.is_black() && kind.is_some()) {
+            return Err(D::Error::custom("empty tiles cannot have a piece kind"));
+        }
+
+        Ok(Playfield {
+            width,
+            height: fill_state.len() / width / 2,
+            fill_state,
+            piece_kinds,
+            lock_times: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::randomizer::{FixedSequence, SequenceEnd};
+    use crate::score::{line_clear_points, perfect_clear_points};
+
+    /// Creates a game on an empty playfield whose active piece is a freshly spawned T-piece.
+    fn game_with_t_piece() -> Game {
+        let mut game = Game::new();
+        let t_piece = PieceData::create_all_pieces()[5].clone();
+        game.spawn_new_piece(t_piece);
+        game
+    }
+
+    /// Gets where the active piece would land if dropped straight down.
+    fn dropped_active_piece(game: &Game) -> ActivePiece {
+        let mut piece = game.active_piece().clone();
+        piece.position.y += game.drop_distance() as i8;
+        piece
+    }
+
+    #[test]
+    fn finesse_optimal_on_simple_targets() {
+        let mut game = game_with_t_piece();
+        let straight_down = Placement::of(&dropped_active_piece(&game));
+        assert_eq!(game.finesse_optimal(straight_down), [GameInput::HardDrop]);
+
+        let two_left = Placement { position: straight_down.position - Vec2I8::new(2, 0), ..straight_down };
+        assert_eq!(game.finesse_optimal(two_left), [GameInput::MoveLeft, GameInput::MoveLeft, GameInput::HardDrop]);
+
+        // Moving the active piece does not change the path from spawn
+        game.move_right();
+        assert_eq!(game.finesse_optimal(straight_down), [GameInput::HardDrop]);
+
+        // A flat T cannot rest in midair
+        let floating = Placement { position: straight_down.position - Vec2I8::new(0, 3), ..straight_down };
+        assert!(game.finesse_optimal(floating).is_empty());
+    }
+
+    #[test]
+    fn finesse_optimal_rotates_into_target() {
+        let mut game = game_with_t_piece();
+        game.rotate_right();
+        let pointing_right = Placement::of(&dropped_active_piece(&game));
+        assert_eq!(game.finesse_optimal(pointing_right), [GameInput::RotateCW, GameInput::HardDrop]);
+
+        game.rotate_right();
+        let flipped = Placement::of(&dropped_active_piece(&game));
+        assert_eq!(game.finesse_optimal(flipped), [GameInput::Rotate180, GameInput::HardDrop]);
+    }
+
+    /// Creates a seeded game with the given rows at the bottom of its playfield, as per [`Playfield::from_ascii()`].
+    fn game_with_board(rows: &str) -> Game {
+        let mut game = Game::with_seed(0);
+        *game.playfield_mut() = Playfield::from_ascii(rows).unwrap();
+        game
+    }
+
+    /// Moves the active piece along the shortest path into a placement and hard drops it there.
+    fn play_path_to(game: &mut Game, target: Placement) -> Option<ClearResult> {
+        let path = game.path_to(target).expect("the placement is not reachable");
+        for &input in &path[..path.len() - 1] {
+            assert!(game.handle_input(input));
+        }
+
+        game.hard_drop()
+    }
+
+    /// Drops a vertical I-piece into the right column of a fresh board whose bottom row only misses that column.
+    fn clear_single_line(game: &mut Game) -> Option<ClearResult> {
+        *game.playfield_mut() = Playfield::from_ascii("#########.").unwrap();
+        game.force_active_piece(PieceKind::I);
+        game.rotate_right();
+        while game.move_right() {}
+        game.hard_drop()
+    }
+
+    /// Gets the kinds of the upcoming pieces of a game.
+    fn next_kinds(game: &Game) -> Vec<PieceKind> {
+        game.next_pieces().iter().map(PieceData::kind).collect()
+    }
+
+    /// Plays a few moves, so the game is not in its initial state anymore.
+    fn play_moves(game: &mut Game) {
+        game.move_left();
+        game.rotate_right();
+        game.hard_drop();
+        game.move_right();
+        game.move_right();
+        game.hard_drop();
+        game.hold_piece();
+    }
+
+    #[test]
+    fn restore_returns_to_snapshot() {
+        let mut game = Game::with_seed(7);
+        play_moves(&mut game);
+
+        let snapshot = game.snapshot();
+        let board = game.playfield().clone();
+        let next = next_kinds(&game);
+
+        play_moves(&mut game);
+        assert!(game.playfield() != &board);
+
+        game.restore(snapshot);
+        assert!(game.playfield() == &board);
+        assert_eq!(next_kinds(&game), next);
+
+        // The randomizer was restored too, so the same pieces follow
+        let mut reference = Game::with_seed(7);
+        play_moves(&mut reference);
+        for _ in 0..10 {
+            game.hard_drop();
+            reference.hard_drop();
+        }
+
+        assert_eq!(next_kinds(&game), next_kinds(&reference));
+    }
+
+    #[test]
+    fn restore_without_randomizer_replays_draws() {
+        let mut game = Game::with_seed(3);
+        play_moves(&mut game);
+
+        // Deserialized snapshots come without their randomizer
+        let mut snapshot = game.snapshot();
+        snapshot.randomizer = None;
+
+        let mut restored = Game::with_seed(3);
+        restored.restore(snapshot);
+
+        for _ in 0..10 {
+            game.hard_drop();
+            restored.hard_drop();
+            assert_eq!(next_kinds(&restored), next_kinds(&game));
+        }
+    }
+
+    #[test]
+    fn scaled_o_piece_covers_4x4_region() {
+        let mut game = Game::with_seed(0);
+        game.restart_with_scale_factor(2);
+        assert_eq!(game.playfield().width(), 5);
+
+        game.force_active_piece(PieceKind::O);
+        game.hard_drop();
+
+        let display = game.display_playfield();
+        assert_eq!(display.width(), 10);
+
+        let set: Vec<_> = (0..display.true_height())
+            .flat_map(|y| (0..display.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| display.has_tile(x, y))
+            .collect();
+        assert_eq!(set.len(), 16);
+
+        let left = set.iter().map(|&(x, _)| x).min().unwrap();
+        let top = set.iter().map(|&(_, y)| y).min().unwrap();
+        assert_eq!(top, display.true_height() - 4);
+        assert!(set.iter().all(|&(x, y)| (left..left + 4).contains(&x) && (top..top + 4).contains(&y)));
+    }
+
+    #[test]
+    fn corner_occupancy_of_t_slot() {
+        let game = game_with_board("
+            .....#####
+            ###...####
+            ####.#####
+        ");
+
+        let mut piece = game.active_piece().clone();
+        piece.rotation = 2;
+        piece.position = Vec2I8::new(3, 37);
+        assert_eq!(game.playfield().corner_occupancy(&piece), [false, true, true, true]);
+
+        // Out of range counts as blocked
+        piece.position = Vec2I8::new(-1, 37);
+        assert_eq!(game.playfield().corner_occupancy(&piece), [true, false, true, true]);
+    }
+
+    #[test]
+    fn t_spin_double_is_detected() {
+        // Dropping the T straight in is blocked, so it has to be spun in
+        let mut game = game_with_board("
+            .....#####
+            ###...####
+            ####.#####
+        ");
+        game.force_active_piece(PieceKind::T);
+
+        let result = play_path_to(&mut game, Placement { rotation: 2, position: Vec2I8::new(3, 37) }).unwrap();
+        assert_eq!(result.kind, ClearKind::TSpin);
+        assert_eq!(result.lines, 2);
+    }
+
+    #[test]
+    fn t_spin_triple_is_detected() {
+        // The overhang blocks the earlier kick tests, so the T kicks down two rows into the slot
+        let mut game = game_with_board("
+            #####.....
+            ####......
+            ####.#####
+            ####..####
+            ####.#####
+        ");
+        game.force_active_piece(PieceKind::T);
+
+        let result = play_path_to(&mut game, Placement { rotation: 1, position: Vec2I8::new(3, 37) }).unwrap();
+        assert_eq!(result.kind, ClearKind::TSpin);
+        assert_eq!(result.lines, 3);
+    }
+
+    #[test]
+    fn stsd_is_detected() {
+        // Like a T-spin triple, but the top row of the slot stays open
+        let mut game = game_with_board("
+            #####.....
+            ####......
+            .....#####
+            ####..####
+            ####.#####
+        ");
+        game.force_active_piece(PieceKind::T);
+
+        let result = play_path_to(&mut game, Placement { rotation: 1, position: Vec2I8::new(3, 37) }).unwrap();
+        assert_eq!(result.kind, ClearKind::TSpin);
+        assert_eq!(result.lines, 2);
+    }
+
+    #[test]
+    fn combo_climbs_and_resets() {
+        let mut game = Game::with_seed(0);
+        assert_eq!(game.combo(), -1);
+
+        // The very first clear starts the combo at 0
+        assert_eq!(clear_single_line(&mut game).unwrap().lines, 1);
+        assert_eq!(game.combo(), 0);
+        clear_single_line(&mut game);
+        assert_eq!(game.combo(), 1);
+        clear_single_line(&mut game);
+        assert_eq!(game.combo(), 2);
+
+        // A lock without a clear breaks it
+        game.force_active_piece(PieceKind::O);
+        assert_eq!(game.hard_drop().unwrap().lines, 0);
+        assert_eq!(game.combo(), -1);
+
+        clear_single_line(&mut game);
+        assert_eq!(game.combo(), 0);
+    }
+
+    #[test]
+    fn back_to_back_needs_consecutive_difficult_clears() {
+        let mut game = game_with_board("
+            #########.
+            #########.
+            #########.
+            #########.
+        ");
+        game.force_active_piece(PieceKind::I);
+        game.rotate_right();
+        while game.move_right() {}
+        assert_eq!(game.hard_drop().unwrap().lines, 4);
+        assert!(game.back_to_back());
+
+        clear_single_line(&mut game);
+        assert!(!game.back_to_back());
+    }
+
+    #[test]
+    fn ghost_lands_on_top_of_column() {
+        let mut game = game_with_board("
+            ....#.....
+            ....#.....
+            ....#.....
+            ....#.....
+            ....#.....
+        ");
+        game.force_active_piece(PieceKind::I);
+
+        let ghost = game.ghost_piece();
+        assert!(ghost.occupied_cells().any(|c| c.x == 4));
+        assert!(ghost.occupied_cells().all(|c| c.y == 34));
+        assert_eq!(game.playfield().drop_distance(&ghost), 0);
+
+        // The ghost of a piece that is already resting is the piece itself
+        game.sonic_drop();
+        assert_eq!(Placement::of(&game.ghost_piece()), Placement::of(game.active_piece()));
+    }
+
+    #[test]
+    fn hard_drop_travels_to_floor_and_scores() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::I);
+
+        // The flat I-piece spawns in the top visible row and falls to the bottom one
+        let row = game.active_piece_cells()[0].y as usize;
+        assert_eq!(row, game.playfield().visible_rows().start);
+        let expected = game.playfield().true_height() - 1 - row;
+        assert_eq!(expected, 19);
+
+        assert_eq!(game.quick_drop(), expected);
+        assert_eq!(game.score(), 2 * expected as u64);
+    }
+
+    #[test]
+    fn soft_drop_scores_per_row() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+        assert!(game.soft_drop());
+        assert!(game.soft_drop());
+
+        assert_eq!(game.score(), 2);
+    }
+
+    #[test]
+    fn lock_delay_resets_at_most_15_times() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+        game.sonic_drop();
+        assert!(game.is_resting());
+
+        for i in 0..MAX_LOCK_RESETS {
+            assert_eq!(game.tick(0.3), None);
+            assert!(game.lock_timer < DEFAULT_LOCK_DELAY);
+
+            let moved = if i % 2 == 0 { game.move_left() } else { game.move_right() };
+            assert!(moved);
+            assert_eq!(game.lock_timer, DEFAULT_LOCK_DELAY);
+        }
+
+        // Once the resets are used up, moving does not extend the delay anymore
+        game.tick(0.3);
+        let remaining = game.lock_timer;
+        assert!(game.move_left());
+        assert_eq!(game.lock_timer, remaining);
+
+        assert!(game.tick(0.3).is_some());
+        assert_eq!(game.pieces_placed(), 1);
+    }
+
+    #[test]
+    fn gravity_accumulates_over_small_steps() {
+        let mut game = Game::with_seed(0);
+        game.set_gravity(2.0);
+        let start = game.active_piece().position.y;
+
+        // 3 seconds at 2 cells per second, in steps that add up exactly
+        for _ in 0..192 {
+            game.tick(1.0 / 64.0);
+        }
+
+        assert_eq!(game.active_piece().position.y - start, 6);
+        assert!(game.drop_progress() < 1.0);
+    }
+
+    #[test]
+    fn infinite_gravity_with_zero_dt_drops_to_floor() {
+        let mut game = Game::with_seed(0);
+        game.set_gravity(f64::INFINITY);
+        game.tick(0.0);
+
+        assert_eq!(game.drop_distance(), 0);
+        assert!(!game.drop_progress().is_nan());
+    }
+
+    #[test]
+    fn non_adjacent_lines_are_found_and_removed() {
+        let mut playfield = Playfield::from_ascii("
+            #.........
+            ##########
+            ..#.......
+            ##########
+            ...#......
+        ").unwrap();
+
+        let rows = playfield.find_completed_lines();
+        assert_eq!(rows, [36, 38]);
+
+        playfield.remove_lines(&rows);
+        let expected = Playfield::from_ascii("
+            #.........
+            ..#.......
+            ...#......
+        ").unwrap();
+        assert!(playfield == expected, "unexpected playfield:\n{}", playfield);
+    }
+
+    #[test]
+    fn rotate_180_kicks_up_in_well() {
+        let mut game = game_with_board("
+            ###....###
+            ###....###
+            ###....###
+            ###....###
+        ");
+        game.force_active_piece(PieceKind::I);
+        game.sonic_drop();
+        assert!(game.active_piece_cells().iter().all(|c| c.y == 39));
+
+        // The flipped I is one row lower than the spawn state, so it has to kick up
+        assert!(game.rotate_180());
+        assert_eq!(game.active_piece().orientation(), Orientation::Flip);
+        assert!(game.active_piece_cells().iter().all(|c| c.y == 39 && (3..7).contains(&c.x)));
+    }
+
+    #[test]
+    fn rotate_180_fails_when_every_kick_is_blocked() {
+        let mut game = game_with_board("
+            ...#.#....
+            ..........
+        ");
+        game.force_active_piece(PieceKind::T);
+        game.active_piece.position = Vec2I8::new(3, 38);
+        assert!(!game.playfield().has_overlap(game.active_piece()));
+
+        assert!(!game.rotate_180());
+        assert_eq!(game.active_piece().rotation, 0);
+        assert_eq!(game.active_piece().position, Vec2I8::new(3, 38));
+    }
+
+    #[test]
+    fn hard_drop_into_full_board_ends_game() {
+        // Every row but the top visible one is filled, without completing any line
+        let mut game = game_with_board(&"#########.\n".repeat(19));
+        game.force_active_piece(PieceKind::T);
+
+        assert_eq!(game.hard_drop(), None);
+        assert!(game.is_game_over());
+        assert_eq!(game.game_over_reason(), Some(GameOverReason::BlockOut));
+
+        // Nothing happens once the game is over
+        assert_eq!(game.hard_drop(), None);
+        assert_eq!(game.pieces_placed(), 1);
+    }
+
+    #[test]
+    fn clearing_to_empty_is_perfect_clear() {
+        let mut game = game_with_board("###....###");
+        game.force_active_piece(PieceKind::I);
+
+        let result = game.hard_drop().unwrap();
+        assert_eq!(result.lines, 1);
+        assert!(result.perfect_clear);
+        assert!(game.playfield().is_empty());
+
+        // Perfect clears award a bonus on top of the single
+        assert_eq!(game.score(), 2 * 19 + line_clear_points(1, ClearKind::Normal) + perfect_clear_points(1));
+    }
+
+    #[test]
+    fn lock_without_clear_is_never_perfect_clear() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::I);
+
+        let result = game.hard_drop().unwrap();
+        assert_eq!(result.lines, 0);
+        assert!(!result.perfect_clear);
+    }
+
+    #[test]
+    fn stack_metrics_of_known_board() {
+        let playfield = Playfield::from_ascii("
+            #.........
+            #.#.......
+            ###......#
+            #.##.....#
+        ").unwrap();
+
+        assert_eq!(playfield.height_profile(), [4, 2, 3, 1, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(playfield.max_height(), 4);
+        assert_eq!(playfield.aggregate_height(), 12);
+        assert_eq!(playfield.bumpiness(), 8);
+        assert_eq!(playfield.hole_count(), 1);
+    }
+
+    #[test]
+    fn stack_metrics_of_empty_board() {
+        let playfield = Playfield::new();
+
+        assert!(playfield.height_profile().iter().all(|&h| h == 0));
+        assert_eq!(playfield.bumpiness(), 0);
+        assert_eq!(playfield.hole_count(), 0);
+    }
+
+    #[test]
+    fn o_piece_has_nine_placements() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::O);
+        let before = Placement::of(game.active_piece());
+
+        let placements = game.enumerate_placements();
+        assert_eq!(placements.len(), 9);
+
+        let columns: BTreeSet<_> = placements.iter().map(|p| p.position.x).collect();
+        assert_eq!(columns.len(), 9);
+
+        // Enumerating does not move the piece
+        assert_eq!(Placement::of(game.active_piece()), before);
+    }
+
+    #[test]
+    fn t_piece_has_placements_in_every_rotation() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+
+        // 8 columns for the flat rotations and 9 for the upright ones
+        assert_eq!(game.enumerate_placements().len(), 34);
+    }
+
+    #[test]
+    fn applying_each_placement_advances_to_next_piece() {
+        let game = game_with_board("
+            ##.....###
+            ###..#####
+        ");
+        let placements = game.enumerate_placements();
+        assert!(!placements.is_empty());
+
+        for placement in placements {
+            let mut copy = game.clone();
+            assert!(copy.apply_placement(placement).is_ok());
+            assert_eq!(copy.pieces_placed(), 1);
+            assert_eq!(copy.active_piece().kind(), game.next_pieces()[0].kind());
+        }
+    }
+
+    #[test]
+    fn invalid_placements_are_rejected() {
+        let mut game = Game::with_seed(0);
+        let resting = Placement::of(&game.ghost_piece());
+
+        assert_eq!(game.apply_placement(Placement { rotation: 4, ..resting }), Err(PlacementError::InvalidRotation));
+        let floating = Placement { position: resting.position - Vec2I8::new(0, 1), ..resting };
+        assert_eq!(game.apply_placement(floating), Err(PlacementError::NotResting));
+        let below = Placement { position: resting.position + Vec2I8::new(0, 1), ..resting };
+        assert_eq!(game.apply_placement(below), Err(PlacementError::Overlaps));
+
+        game.set_paused(true);
+        assert_eq!(game.apply_placement(resting), Err(PlacementError::Paused));
+        assert_eq!(game.pieces_placed(), 0);
+
+        game.set_paused(false);
+        assert!(game.apply_placement(resting).is_ok());
+
+        let mut lost = game_with_board(&"#########.\n".repeat(19));
+        lost.hard_drop();
+        assert!(lost.is_game_over());
+        assert_eq!(lost.apply_placement(resting), Err(PlacementError::GameOver));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn identical_boards_are_equal_and_hash_equal() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash_of = |playfield: &Playfield| {
+            let mut hasher = DefaultHasher::new();
+            playfield.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let rows = "
+            ..##......
+            .####...#.
+        ";
+        let a = Playfield::from_ascii(rows).unwrap();
+        let mut b = Playfield::new();
+        b.set_cells(&[(2, 38), (3, 38), (1, 39), (2, 39), (3, 39), (4, 39), (8, 39)], GARBAGE_COLOR);
+
+        assert!(a == b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        b.set_tile(0, 0, GARBAGE_COLOR);
+        assert!(a != b);
+    }
+
+    #[test]
+    fn occupancy_eq_ignores_colors() {
+        let a = Playfield::from_ascii("#.#.......").unwrap();
+        let mut b = Playfield::new();
+        b.set_cells(&[(0, 39), (2, 39)], Color::WHITE);
+
+        assert!(a != b);
+        assert!(a.occupancy_eq(&b));
+
+        b.set_tile(2, 39, Color::BLACK);
+        assert!(!a.occupancy_eq(&b));
+    }
+
+    #[test]
+    fn garbage_keeps_its_hole_open() {
+        let mut game = game_with_board("##........");
+        assert!(!game.receive_garbage(3, 4));
+
+        let board = game.playfield();
+        for y in 37..40 {
+            for x in 0..10 {
+                assert_eq!(board.get_tile(x, y).is_black(), x == 4, "tile ({}, {})", x, y);
+            }
+        }
+
+        // The old bottom row was pushed up above the garbage
+        assert!(!board.get_tile(0, 36).is_black());
+        assert!(!board.get_tile(1, 36).is_black());
+        assert!(board.get_tile(2, 36).is_black());
+    }
+
+    #[test]
+    fn garbage_into_the_active_piece_tops_out() {
+        let mut game = Game::with_seed(0);
+        assert!(game.receive_garbage(40, 0));
+        assert_eq!(game.game_over_reason(), Some(GameOverReason::TopOut));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "hole column")]
+    fn garbage_hole_past_the_wall_panics() {
+        Playfield::new().add_garbage_lines(1, 10);
+    }
+
+    #[test]
+    fn pieces_spawn_centered_on_narrow_board() {
+        let config = PlayfieldConfig { width: 6, height: 20 };
+        let mut game = Game::with_config_and_randomizer(config, Box::new(SevenBag::with_seed(0)));
+        assert_eq!(game.playfield().width(), 6);
+
+        for kind in PieceKind::ALL {
+            game.force_active_piece(kind);
+            let cells = game.active_piece_cells();
+            let left = cells.iter().map(|c| c.x).min().unwrap();
+            let right = cells.iter().map(|c| c.x).max().unwrap();
+
+            // Pieces with odd widths lean to the left, like on the guideline playfield
+            let gap_left = left;
+            let gap_right = 5 - right;
+            assert!(gap_left >= 0 && gap_right >= 0, "{:?} spawned outside the board", kind);
+            assert!(gap_right - gap_left == 0 || gap_right - gap_left == 1, "{:?} is not centered", kind);
+        }
+    }
+
+    #[test]
+    fn bounds_follow_configured_width() {
+        let board = Playfield::with_size(PlayfieldConfig { width: 6, height: 20 });
+        assert!(board.is_in_bounds(0, 0));
+        assert!(board.is_in_bounds(5, 39));
+        assert!(!board.is_in_bounds(6, 0));
+        assert!(!board.is_in_bounds(0, 40));
+    }
+
+    #[test]
+    fn occupied_cells_of_rotated_t() {
+        let t = PieceData::create_all_pieces()[PieceKind::T.index()].clone();
+        let mut piece = ActivePiece::new(t, Vec2I8::new(3, 10));
+        piece.rotation = 1;
+
+        let cells: Vec<Vec2I8> = piece.occupied_cells().collect();
+        let expected = [Vec2I8::new(4, 10), Vec2I8::new(4, 11), Vec2I8::new(4, 12), Vec2I8::new(5, 11)];
+        assert_eq!(cells, expected);
+
+        // Off the left wall, the coordinates go negative instead of being clipped
+        piece.position = Vec2I8::new(-1, 10);
+        assert_eq!(piece.occupied_cells().filter(|c| c.x < 0).count(), 0);
+        piece.position = Vec2I8::new(-2, 10);
+        assert_eq!(piece.occupied_cells().filter(|c| c.x < 0).count(), 3);
+    }
+
+    #[test]
+    fn display_shows_locked_piece() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+        game.hard_drop();
+
+        let expected = "..........\n".repeat(18) + "....#.....\n...###....";
+        assert_eq!(game.playfield().to_string(), expected);
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let rows = "....#.....\n##.####.##\n#########.";
+        let config = PlayfieldConfig { width: 10, height: 3 };
+        let board = Playfield::from_ascii_with_size(config, rows).unwrap();
+        assert_eq!(board.to_ascii(false), rows);
+        assert_eq!(board.to_ascii(true), "..........\n".repeat(3) + rows);
+    }
+
+    #[test]
+    fn invalid_ascii_is_rejected() {
+        assert_eq!(
+            Playfield::from_ascii("#.#").err(),
+            Some(PlayfieldParseError::WidthMismatch { row: 0, expected: 10, found: 3 })
+        );
+        assert_eq!(
+            Playfield::from_ascii("....x.....").err(),
+            Some(PlayfieldParseError::InvalidChar { row: 0, column: 4, found: 'x' })
+        );
+        assert_eq!(
+            Playfield::from_ascii(&"..........\n".repeat(41)).err(),
+            Some(PlayfieldParseError::TooManyRows { expected: 40, found: 41 })
+        );
+    }
+
+    /// Creates a game on the guideline playfield that draws the given pieces in a cycle.
+    fn game_with_sequence(kinds: &[PieceKind]) -> Game {
+        let randomizer = FixedSequence::new(kinds.to_vec(), SequenceEnd::Cycle);
+        Game::with_config_and_randomizer(PlayfieldConfig::guideline(), Box::new(randomizer))
+    }
+
+    #[test]
+    fn piece_counts_follow_scripted_sequence() {
+        let mut game = game_with_sequence(&[PieceKind::I, PieceKind::O, PieceKind::T, PieceKind::T, PieceKind::S]);
+        for _ in 0..4 {
+            game.hard_drop();
+        }
+
+        let mut expected = [0; PIECE_COUNT];
+        expected[PieceKind::I.index()] = 1;
+        expected[PieceKind::O.index()] = 1;
+        expected[PieceKind::T.index()] = 2;
+        expected[PieceKind::S.index()] = 1;
+
+        assert_eq!(game.piece_counts(), expected);
+        assert_eq!(game.pieces_placed(), 4);
+    }
+
+    #[test]
+    fn elapsed_time_and_pps_follow_ticks() {
+        let mut game = Game::with_seed(0);
+        game.hard_drop();
+        assert_eq!(game.pieces_per_second(), 0.0);
+
+        for _ in 0..4 {
+            game.tick(0.25);
+        }
+
+        game.hard_drop();
+        game.hard_drop();
+        game.tick(0.5);
+        game.tick(0.5);
+
+        assert_eq!(game.elapsed_seconds(), 2.0);
+        assert_eq!(game.pieces_placed(), 3);
+        assert_eq!(game.pieces_per_second(), 1.5);
+    }
+
+    #[test]
+    fn paused_game_ignores_ticks_and_inputs() {
+        let mut game = Game::with_seed(0);
+        let before = game.active_piece().clone();
+
+        game.set_paused(true);
+        for _ in 0..100 {
+            assert!(game.tick(1.0).is_none());
+        }
+
+        assert!(!game.handle_input(GameInput::MoveLeft));
+        assert!(!game.handle_input(GameInput::HardDrop));
+        assert!(game.finish_piece_turn().is_none());
+        assert_eq!(game.active_piece().position, before.position);
+        assert_eq!(game.active_piece().rotation, before.rotation);
+        assert_eq!(game.elapsed_seconds(), 0.0);
+        assert_eq!(game.pieces_placed(), 0);
+
+        game.set_paused(false);
+        game.tick(1.0);
+        assert!(game.active_piece().position != before.position);
+    }
+
+    #[test]
+    fn reset_empties_board_and_restarts_sequence() {
+        let mut game = Game::with_seed(3);
+        for _ in 0..5 {
+            game.hard_drop();
+        }
+
+        assert!(!game.playfield().is_empty());
+        game.reset();
+
+        let fresh = Game::with_seed(3);
+        assert!(game.playfield().is_empty());
+        assert_eq!(game.active_piece().kind(), fresh.active_piece().kind());
+        assert_eq!(next_kinds(&game), next_kinds(&fresh));
+        assert_eq!(game.pieces_placed(), 0);
+        assert_eq!(game.score(), 0);
+    }
+
+    #[test]
+    fn hold_outcomes() {
+        let mut game = game_with_sequence(&[PieceKind::T, PieceKind::I, PieceKind::O, PieceKind::S]);
+
+        assert_eq!(game.hold_piece(), HoldOutcome::FilledFromQueue(PieceKind::I));
+        assert_eq!(game.active_piece().kind(), PieceKind::I);
+
+        // Holding again before placing the piece does nothing
+        assert_eq!(game.hold_piece(), HoldOutcome::Blocked);
+        assert_eq!(game.active_piece().kind(), PieceKind::I);
+        assert_eq!(game.held_piece().map(PieceData::kind), Some(PieceKind::T));
+
+        game.hard_drop();
+        assert_eq!(game.active_piece().kind(), PieceKind::O);
+        assert_eq!(game.hold_piece(), HoldOutcome::Swapped(PieceKind::T));
+        assert_eq!(game.active_piece().kind(), PieceKind::T);
+        assert_eq!(game.held_piece().map(PieceData::kind), Some(PieceKind::O));
+    }
+
+    #[test]
+    fn spawning_into_the_stack_is_block_out() {
+        let mut game = game_with_sequence(&[PieceKind::O, PieceKind::T]);
+        while game.move_left() {}
+
+        // Fill the visible rows below the spawn area, leaving room for the O-piece on the left
+        *game.playfield_mut() = Playfield::from_ascii(&"..######..\n".repeat(20)).unwrap();
+        assert!(!game.is_game_over());
+
+        game.hard_drop();
+        assert_eq!(game.game_over_reason(), Some(GameOverReason::BlockOut));
+        assert!(game.finish_piece_turn().is_none());
+    }
+
+    #[test]
+    fn locking_above_the_visible_rows_is_lock_out() {
+        let mut game = Game::with_seed(0);
+        game.set_spawn_row_nudge(false);
+        game.force_active_piece(PieceKind::I);
+
+        // Stack up to the ceiling, so the flat I-piece locks in the row it spawned in
+        *game.playfield_mut() = Playfield::from_ascii(&"#########.\n".repeat(20)).unwrap();
+        assert!(game.active_piece_cells().iter().all(|c| c.y == 19));
+
+        assert!(game.hard_drop().is_none());
+        assert_eq!(game.game_over_reason(), Some(GameOverReason::LockOut));
+    }
+
+    #[test]
+    fn input_sequence_builds_expected_board() {
+        use GameInput::*;
+
+        let mut game = game_with_sequence(&[PieceKind::T, PieceKind::I]);
+        let inputs = [RotateCW, MoveLeft, MoveLeft, MoveLeft, MoveLeft, HardDrop, MoveRight, MoveRight, MoveRight, HardDrop];
+        for input in inputs {
+            assert!(game.handle_input(input), "{:?} had no effect", input);
+        }
+
+        let expected = Playfield::from_ascii("
+            #.........
+            ##........
+            #.....####
+        ").unwrap();
+        assert!(game.playfield().occupancy_eq(&expected));
+
+        // Moving into the wall has no effect
+        while game.handle_input(MoveLeft) {}
+        assert!(!game.handle_input(MoveLeft));
+    }
+
+    #[test]
+    fn auto_shift_waits_for_das_then_repeats_at_arr() {
+        let mut game = game_with_sequence(&[PieceKind::T]);
+        game.set_das(0.25);
+        game.set_arr(0.125);
+        let start = game.active_piece().position.x;
+        let moved = |game: &Game| game.active_piece().position.x - start;
+
+        assert!(game.hold_direction(HorizontalDir::Right));
+        assert_eq!(moved(&game), 1);
+
+        game.tick(0.125);
+        assert_eq!(moved(&game), 1);
+        game.tick(0.125);
+        assert_eq!(moved(&game), 2);
+
+        // A long tick repeats several times, but stops at the wall
+        game.tick(0.25);
+        assert_eq!(moved(&game), 4);
+        game.tick(1.0);
+        assert_eq!(moved(&game), 4);
+
+        game.release_direction(HorizontalDir::Right);
+        assert!(game.hold_direction(HorizontalDir::Left));
+        game.release_direction(HorizontalDir::Left);
+        game.tick(1.0);
+        assert_eq!(moved(&game), 3);
+    }
+
+    #[test]
+    fn zero_arr_slides_to_the_wall_after_das() {
+        let mut game = game_with_sequence(&[PieceKind::T]);
+        game.set_das(0.25);
+        game.set_arr(0.0);
+        let start = game.active_piece().position.x;
+
+        game.hold_direction(HorizontalDir::Left);
+        game.tick(0.125);
+        assert_eq!(game.active_piece().position.x, start - 1);
+
+        game.tick(0.125);
+        assert_eq!(game.active_piece().position.x, start - 3);
+    }
+
+    #[test]
+    fn double_hold_returns_pieces_in_order() {
+        use PieceKind::*;
+
+        let mut game = game_with_sequence(&[T, I, O, S, Z, J, L]);
+        game.set_hold_capacity(2);
+
+        assert_eq!(game.hold_piece(), HoldOutcome::FilledFromQueue(I));
+        game.hard_drop();
+        assert_eq!(game.hold_piece(), HoldOutcome::FilledFromQueue(S));
+
+        let held: Vec<PieceKind> = game.held_pieces().iter().map(PieceData::kind).collect();
+        assert_eq!(held, [T, O]);
+
+        game.hard_drop();
+        assert_eq!(game.hold_piece(), HoldOutcome::Swapped(T));
+        game.hard_drop();
+        assert_eq!(game.hold_piece(), HoldOutcome::Swapped(O));
+
+        let held: Vec<PieceKind> = game.held_pieces().iter().map(PieceData::kind).collect();
+        assert_eq!(held, [Z, J]);
+    }
+
+    #[test]
+    fn visible_range_excludes_buffer() {
+        let mut board = Playfield::new();
+        let visible = board.visible_rows();
+        assert_eq!(visible.len(), PLAYFIELD_HEIGHT);
+        assert_eq!(visible.end, TRUE_PLAYFIELD_HEIGHT);
+
+        assert!(!board.is_visible(PLAYFIELD_HEIGHT - 1));
+        assert!(board.is_visible(PLAYFIELD_HEIGHT));
+        assert!(board.is_visible(TRUE_PLAYFIELD_HEIGHT - 1));
+        assert!(!board.is_visible(TRUE_PLAYFIELD_HEIGHT));
+
+        // Buffer tiles are left out of the visible tiles, and their y starts at the top visible row
+        board.set_cells(&[(0, PLAYFIELD_HEIGHT - 1), (1, PLAYFIELD_HEIGHT)], GARBAGE_COLOR);
+        let filled: Vec<(usize, usize)> = board.visible_tiles().filter(|t| !t.2.is_black()).map(|t| (t.0, t.1)).collect();
+        assert_eq!(filled, [(1, 0)]);
+        assert_eq!(board.visible_tiles().count(), PLAYFIELD_WIDTH * PLAYFIELD_HEIGHT);
+    }
+
+    #[test]
+    fn pieces_without_kicks_only_rotate_in_place() {
+        use crate::pieces::KickTable;
+
+        let srs = PieceData::create_all_pieces()[PieceKind::T.index()].clone();
+        let cells: Vec<Vec2I8> = srs.state(0).iter_cells().map(|(x, y)| Vec2I8::new(x as i8, y as i8)).collect();
+        let no_kicks = PieceData::from_cells(PieceKind::T, &cells, srs.color(), KickTable::none());
+
+        // A vertical T flat against the left wall can only turn to face down by kicking right
+        let board = Playfield::new();
+        for (piece_data, kicks) in [(srs, true), (no_kicks, false)] {
+            let mut piece = ActivePiece::new(piece_data, Vec2I8::new(-1, 30));
+            piece.rotation = 1;
+            assert!(!board.has_overlap(&piece));
+
+            let (trg_rot, kick_tests) = piece.right_rotation();
+            let rotated = board.rotated_piece(&piece, trg_rot, &kick_tests);
+            assert_eq!(rotated.is_some(), kicks);
+
+            if let Some((rotated, kick_used)) = rotated {
+                assert!(kick_used);
+                assert_eq!(rotated.position, Vec2I8::new(0, 30));
+            }
+        }
+    }
+
+    #[test]
+    fn can_move_matches_actual_moves() {
+        use GameInput::*;
+
+        let check = |game: &Game| {
+            for input in [MoveLeft, MoveRight, SoftDrop, HardDrop, RotateCW, RotateCCW, Rotate180, Hold] {
+                let mut copy = game.clone();
+                assert_eq!(game.can_move(input), copy.handle_input(input), "{:?}", input);
+            }
+        };
+
+        let mut game = game_with_board("
+            ..........
+            #.........
+            ##.......#
+        ");
+        check(&game);
+
+        // Against the left wall
+        game.force_active_piece(PieceKind::J);
+        while game.move_left() {}
+        check(&game);
+
+        // Resting on the stack, after holding
+        game.hold_piece();
+        game.rotate_right();
+        while game.soft_drop() {}
+        check(&game);
+
+        // In a narrow well that blocks most rotations
+        *game.playfield_mut() = Playfield::from_ascii(&"###.######\n".repeat(6)).unwrap();
+        game.force_active_piece(PieceKind::I);
+        game.rotate_right();
+        while game.active_piece_cells()[0].x > 3 {
+            game.move_left();
+        }
+
+        while game.soft_drop() {}
+        assert_eq!(game.active_piece_cells()[3].y, 39);
+        check(&game);
+
+        game.set_paused(true);
+        check(&game);
+    }
+
+    #[test]
+    fn row_accessors_and_clear() {
+        let mut board = Playfield::from_ascii("
+            ..........
+            ##.#######
+            ##########
+        ").unwrap();
+
+        assert_eq!(board.row(39), &[GARBAGE_COLOR; 10]);
+        assert!(board.row(38)[2].is_black());
+
+        assert!(board.is_row_full(39));
+        assert!(!board.is_row_empty(39));
+        assert!(!board.is_row_full(38));
+        assert!(!board.is_row_empty(38));
+        assert!(board.is_row_empty(37));
+        assert!(!board.is_row_full(37));
+
+        board.clear();
+        assert!(board.is_empty());
+        assert!((0..40).all(|y| board.is_row_empty(y)));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn row_out_of_range_panics() {
+        Playfield::new().row(40);
+    }
+
+    #[test]
+    fn tiles_out_of_range_are_left_alone() {
+        let mut board = Playfield::new();
+        assert!(!board.set_tile(10, 0, Color::WHITE));
+        assert!(!board.set_tile(0, 40, Color::WHITE));
+        assert!(board.get_tile_mut(10, 39).is_none());
+        assert!(board.is_empty());
+
+        // Reading out of range acts like a wall
+        assert_eq!(board.get_tile(10, 0), Color::WHITE);
+        assert!(board.has_tile_signed(-1, 39));
+        assert!(!board.has_tile_signed(0, 39));
+
+        assert!(board.set_tile(9, 39, Color::WHITE));
+        assert_eq!(board.get_tile(9, 39), Color::WHITE);
+    }
+
+    #[test]
+    fn pieces_spawn_in_guideline_columns() {
+        let mut game = Game::with_seed(0);
+        for kind in PieceKind::ALL {
+            game.force_active_piece(kind);
+            let cells = game.active_piece_cells();
+            let columns = (cells.iter().map(|c| c.x).min().unwrap(), cells.iter().map(|c| c.x).max().unwrap());
+            let expected = match kind {
+                PieceKind::I => (3, 6),
+                PieceKind::O => (4, 5),
+                _ => (3, 5)
+            };
+
+            assert_eq!(columns, expected, "{:?}", kind);
+            assert_eq!(game.active_piece().rotation, Orientation::Spawn.index());
+        }
+    }
+
+    #[test]
+    fn next_piece_kinds_match_queue() {
+        let mut game = Game::with_seed(11);
+        assert_eq!(game.next_piece_kinds(3), next_kinds(&game)[..3]);
+        assert_eq!(game.next_piece_kinds(0), []);
+
+        // Only the preview is known in advance
+        assert_eq!(game.next_piece_kinds(100), next_kinds(&game));
+        assert_eq!(game.next_piece_kinds(100).len(), game.preview_count());
+
+        let upcoming = game.next_piece_kinds(1)[0];
+        game.hard_drop();
+        assert_eq!(game.active_piece().kind(), upcoming);
+    }
+
+    #[test]
+    fn preview_count_holds_throughout_play() {
+        let mut game = Game::with_seed(0);
+        game.set_preview_count(3);
+        assert_eq!(game.next_pieces().len(), 3);
+
+        for i in 0..8 {
+            if i % 3 == 0 {
+                game.hold_piece();
+            }
+
+            game.hard_drop();
+            assert!(!game.is_game_over());
+            assert_eq!(game.next_pieces().len(), 3);
+        }
+
+        game.reset();
+        assert_eq!(game.next_pieces().len(), 3);
+    }
+
+    #[test]
+    fn clones_stay_in_lockstep() {
+        let mut game = Game::with_seed(21);
+        play_moves(&mut game);
+        let mut copy = game.clone();
+
+        for i in 0..6 {
+            for g in [&mut game, &mut copy] {
+                if i % 2 == 0 {
+                    g.move_left();
+                } else {
+                    g.rotate_right();
+                }
+
+                g.tick(0.25);
+                g.hard_drop();
+            }
+
+            assert!(game.playfield() == copy.playfield());
+            assert_eq!(game.active_piece().kind(), copy.active_piece().kind());
+            assert_eq!(next_kinds(&game), next_kinds(&copy));
+            assert_eq!(game.score(), copy.score());
+        }
+
+        // Changing the clone leaves the original alone
+        assert!(!copy.is_game_over());
+        copy.hard_drop();
+        assert!(game.playfield() != copy.playfield());
+    }
+
+    #[test]
+    fn path_to_rotates_then_hard_drops() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+
+        let mut target = game.active_piece().clone();
+        target.rotation = 1;
+        target.position.y += game.playfield().drop_distance(&target) as i8;
+
+        assert_eq!(game.path_to(Placement::of(&target)), Some(vec![GameInput::RotateCW, GameInput::HardDrop]));
+
+        // Sealed off under a full row, the bottom cannot be reached
+        *game.playfield_mut() = Playfield::from_ascii("##########\n..........").unwrap();
+        game.force_active_piece(PieceKind::I);
+
+        let mut buried = game.active_piece().clone();
+        buried.position.y = 38;
+        assert!(!game.playfield().has_overlap(&buried));
+        assert_eq!(game.path_to(Placement::of(&buried)), None);
+    }
+
+    #[test]
+    fn sonic_drop_keeps_piece_active() {
+        let mut game = game_with_board(&("...####...\n".to_owned() + &"..........\n".repeat(9)));
+        game.force_active_piece(PieceKind::T);
+        let kind = game.active_piece().kind();
+
+        let distance = game.sonic_drop();
+        assert!(distance > 0);
+        assert!(game.is_resting());
+        assert_eq!(game.active_piece_cells().iter().map(|c| c.y).max(), Some(29));
+
+        assert_eq!(game.active_piece().kind(), kind);
+        assert_eq!(game.pieces_placed(), 0);
+        assert_eq!(game.sonic_drop(), 0);
+
+        // It can still slide off the platform
+        assert!(game.move_left());
+    }
+
+    #[test]
+    fn overlap_at_the_left_wall() {
+        let i = PieceData::create_all_pieces()[PieceKind::I.index()].clone();
+        let mut piece = ActivePiece::new(i, Vec2I8::new(-2, 30));
+        piece.rotation = 1;
+
+        // The matrix starts left of the wall, but the blocks are all in column 0
+        let mut board = Playfield::new();
+        assert!(piece.occupied_cells().all(|c| c.x == 0));
+        assert!(!board.has_overlap(&piece));
+
+        // Negative columns must not wrap around to the right side of the previous row
+        board.set_cells(&[(9, 29), (9, 30), (9, 31), (9, 32)], GARBAGE_COLOR);
+        piece.position.x = -3;
+        assert!(board.has_overlap(&piece));
+        piece.position.x = -2;
+        assert!(!board.has_overlap(&piece));
+
+        board.set_tile(0, 33, GARBAGE_COLOR);
+        assert!(board.has_overlap(&piece));
+    }
+
+    #[test]
+    fn collision_kind_tells_walls_from_blocks() {
+        let board = Playfield::from_ascii("
+            ..........
+            ...###....
+        ").unwrap();
+        let mut game = Game::with_seed(0);
+        *game.playfield_mut() = board.clone();
+        game.force_active_piece(PieceKind::O);
+        let spawned = game.active_piece().clone();
+
+        // Pushed into the right wall
+        while game.move_right() {}
+        let mut piece = game.active_piece().clone();
+        assert_eq!(board.collision_kind(&piece), None);
+        piece.position.x += 1;
+        assert_eq!(board.collision_kind(&piece), Some(CollisionKind::Wall));
+
+        // Resting on the stacked blocks
+        let mut piece = spawned;
+        piece.position.y += board.drop_distance(&piece) as i8;
+        assert_eq!(board.collision_kind(&piece), None);
+        piece.position.y += 1;
+        assert_eq!(board.collision_kind(&piece), Some(CollisionKind::Block));
+
+        // Past the floor
+        piece.position.x = 7;
+        piece.position.y += board.drop_distance(&piece) as i8 + 1;
+        assert_eq!(board.collision_kind(&piece), Some(CollisionKind::Wall));
+    }
+
+    #[test]
+    fn mirroring_board_twice_is_identity() {
+        let original = Playfield::from_ascii("
+            #.........
+            ##..#.....
+            ###.######
+        ").unwrap();
+
+        let mut board = original.clone();
+        board.mirror_horizontal();
+        assert!(board == Playfield::from_ascii("
+            .........#
+            .....#..##
+            ######.###
+        ").unwrap());
+
+        board.mirror_horizontal();
+        assert!(board == original);
+    }
+
+    #[test]
+    fn locked_cells_age_with_play_time() {
+        let mut game = game_with_board("#########.");
+        game.set_tracks_cell_ages(true);
+        game.force_active_piece(PieceKind::T);
+        game.tick(0.5);
+        game.hard_drop();
+
+        // The T rests on the garbage, with its flat side in row 38
+        assert_eq!(game.cell_age(4, 38), Some(0.0));
+        game.tick(1.5);
+        assert_eq!(game.cell_age(4, 38), Some(1.5));
+
+        game.set_paused(true);
+        game.tick(10.0);
+        assert_eq!(game.cell_age(4, 38), Some(1.5));
+
+        // Garbage and empty tiles have no age
+        assert_eq!(game.cell_age(0, 39), None);
+        assert_eq!(game.cell_age(0, 38), None);
+
+        game.set_tracks_cell_ages(false);
+        assert_eq!(game.cell_age(4, 38), None);
+    }
+
+    #[test]
+    fn stack_height_follows_tallest_column() {
+        let mut game = game_with_sequence(&[PieceKind::I]);
+        assert_eq!(game.stack_height(), 0);
+
+        game.hard_drop();
+        assert_eq!(game.stack_height(), 1);
+
+        // Stand the next I-pieces up at the left wall
+        for expected in [4, 8] {
+            game.rotate_right();
+            while game.move_left() {}
+            game.hard_drop();
+            assert_eq!(game.stack_height(), expected);
+        }
+
+        assert!(game.is_in_danger(8));
+        assert!(!game.is_in_danger(9));
+    }
+
+    #[test]
+    fn content_hash_only_sees_occupancy() {
+        let a = Playfield::from_ascii("##..#.....").unwrap();
+        let mut b = Playfield::new();
+        b.set_cells(&[(0, 39), (1, 39), (4, 39)], Color::WHITE);
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        b.set_tile(4, 39, Color::BLACK);
+        assert!(a.content_hash() != b.content_hash());
+        assert!(Playfield::new().content_hash() != Playfield::with_size(PlayfieldConfig { width: 10, height: 21 }).content_hash());
+    }
+
+    #[test]
+    fn bitboard_round_trip() {
+        let mut game = game_with_board("
+            #.........
+            ##..#....#
+        ");
+        game.force_active_piece(PieceKind::T);
+        game.hard_drop();
+
+        let bits = game.board_as_bitboard();
+        assert_eq!(bits.len(), 20);
+        assert_eq!(bits[19], 0b10_0001_0011);
+        assert_eq!(bits[18], 0b00_0011_1001);
+        assert_eq!(bits[17], 0b00_0001_0000);
+
+        let restored = Playfield::from_bitboard(10, &bits);
+        assert!(restored.occupancy_eq(game.playfield()));
+    }
+
+    #[test]
+    fn spawn_row_nudge_lowers_pieces_by_one_row() {
+        let mut game = Game::with_seed(0);
+        assert!(game.spawn_row_nudge());
+        game.force_active_piece(PieceKind::J);
+        let nudged = game.active_piece().position;
+
+        game.set_spawn_row_nudge(false);
+        game.force_active_piece(PieceKind::J);
+        assert_eq!(game.active_piece().position, nudged - Vec2I8::new(0, 1));
+
+        // The top row of the J starts just above the visible playfield
+        let top = game.active_piece_cells().iter().map(|c| c.y).min().unwrap();
+        assert_eq!(top, game.playfield().height() as i8 - 1);
+    }
+
+    #[test]
+    fn drop_progress_is_fraction_of_a_row() {
+        let mut game = Game::with_seed(0);
+        game.set_gravity(2.0);
+        let y = game.active_piece().position.y;
+        assert_eq!(game.drop_progress(), 0.0);
+
+        game.tick(0.125);
+        assert_eq!(game.drop_progress(), 0.25);
+        game.tick(0.25);
+        assert_eq!(game.drop_progress(), 0.75);
+        assert_eq!(game.active_piece().position.y, y);
+
+        // Passing the row boundary moves the piece and keeps the rest of the progress
+        game.tick(0.25);
+        assert_eq!(game.active_piece().position.y, y + 1);
+        assert_eq!(game.drop_progress(), 0.25);
+
+        game.sonic_drop();
+        assert_eq!(game.drop_progress(), 0.0);
+    }
+
+    #[test]
+    fn cleared_garbage_is_counted() {
+        let mut game = game_with_sequence(&[PieceKind::I]);
+        game.receive_garbage(2, 0);
+
+        // A vertical I-piece in the hole clears both garbage rows, and its own blocks in them don't count
+        game.rotate_right();
+        while game.move_left() {}
+        let result = game.hard_drop().unwrap();
+        assert_eq!(result.lines, 2);
+        assert_eq!(result.garbage_cleared, 18);
+        assert_eq!(game.playfield().count_garbage_cells(&[38, 39]), 0);
+    }
+
+    #[test]
+    fn holding_i_with_t_next_makes_t_active() {
+        let mut game = game_with_sequence(&[PieceKind::I, PieceKind::T, PieceKind::O]);
+
+        let preview = game.preview_after_hold().unwrap();
+        assert_eq!(preview.kind(), PieceKind::T);
+
+        let outcome = game.hold_piece();
+        assert_eq!(outcome.active_kind(), Some(PieceKind::T));
+        assert_eq!(game.active_piece().kind(), PieceKind::T);
+        assert_eq!(game.active_piece().position, preview.position);
+
+        // Blocked holds have no active kind and nothing to preview
+        assert_eq!(game.preview_after_hold().map(|piece| piece.kind()), None);
+        assert_eq!(game.hold_piece().active_kind(), None);
+    }
+
+    #[test]
+    fn extended_delay_locks_after_lock_delay() {
+        let mut game = Game::with_seed(0);
+        assert_eq!(game.lock_mode(), LockMode::ExtendedDelay);
+        game.set_lock_delay(0.5);
+        game.sonic_drop();
+
+        // Failing to move down does not lock by itself
+        assert!(!game.soft_drop());
+        assert!(game.tick(0.25).is_none());
+        assert_eq!(game.pieces_placed(), 0);
+
+        assert!(game.tick(0.25).is_some());
+        assert_eq!(game.pieces_placed(), 1);
+    }
+
+    #[test]
+    fn classic_mode_locks_after_a_failed_move_down() {
+        let mut game = Game::with_seed(0);
+        game.set_lock_mode(LockMode::Classic);
+        game.set_gravity(0.0);
+
+        // The soft drop that fails at the bottom locks the piece on the next tick
+        game.sonic_drop();
+        assert_eq!(game.pieces_placed(), 0);
+        assert!(game.tick(0.0).is_some());
+        assert_eq!(game.pieces_placed(), 1);
+
+        // Gravity reaching the stack does not lock, but the following gravity step that fails does
+        game.set_gravity(1.0);
+        for _ in 0..game.drop_distance() {
+            assert!(game.tick(1.0).is_none());
+        }
+
+        assert!(game.is_resting());
+        assert_eq!(game.pieces_placed(), 1);
+        assert!(game.tick(1.0).is_some());
+        assert_eq!(game.pieces_placed(), 2);
+    }
+
+    #[test]
+    fn bounding_boxes_of_i_and_t() {
+        let pieces = PieceData::create_all_pieces();
+
+        let mut i = ActivePiece::new(pieces[PieceKind::I.index()].clone(), Vec2I8::new(3, 10));
+        i.rotation = 1;
+        assert_eq!(i.bounding_box(), (Vec2I8::new(5, 10), Vec2I8::new(5, 13)));
+
+        let t = ActivePiece::new(pieces[PieceKind::T.index()].clone(), Vec2I8::new(3, 10));
+        assert_eq!(t.bounding_box(), (Vec2I8::new(3, 10), Vec2I8::new(5, 11)));
+    }
+
+    #[test]
+    fn rotating_right_cycles_orientations() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+        assert_eq!(game.active_piece().orientation(), Orientation::Spawn);
+
+        for expected in [Orientation::Right, Orientation::Flip, Orientation::Left, Orientation::Spawn] {
+            assert!(game.rotate_right());
+            assert_eq!(game.active_piece().orientation(), expected);
+        }
+
+        assert!(game.rotate_left());
+        assert_eq!(game.active_piece().orientation(), Orientation::Left);
+        assert_eq!(Orientation::from_index(4), None);
+    }
+
+    #[test]
+    fn controls_are_locked_during_are() {
+        let mut game = game_with_sequence(&[PieceKind::T, PieceKind::O]);
+        game.set_are(0.5);
+        game.hard_drop();
+
+        // The locked T stays the active piece and cannot be controlled
+        assert!(game.is_in_are());
+        assert_eq!(game.active_piece().kind(), PieceKind::T);
+        let locked = game.active_piece().position;
+        for input in [GameInput::MoveLeft, GameInput::RotateCW, GameInput::SoftDrop, GameInput::Hold] {
+            assert!(!game.handle_input(input), "{:?} had an effect", input);
+        }
+
+        assert!(game.hard_drop().is_none());
+        assert_eq!(game.active_piece().position, locked);
+        assert_eq!(game.pieces_placed(), 1);
+
+        game.tick(0.25);
+        assert_eq!(game.are_remaining(), Some(0.25));
+        game.tick(0.25);
+        assert!(!game.is_in_are());
+        assert_eq!(game.active_piece().kind(), PieceKind::O);
+        assert!(game.move_left());
+    }
+
+    #[test]
+    fn buffered_rotation_applies_on_spawn() {
+        let mut game = game_with_sequence(&[PieceKind::T, PieceKind::J, PieceKind::L]);
+        game.set_are(0.5);
+        game.set_input_buffering(true);
+        game.hard_drop();
+
+        assert!(game.handle_input(GameInput::RotateCW));
+        assert!(game.handle_input(GameInput::Hold));
+        game.tick(0.5);
+
+        // The hold goes first, so the rotation applies to the piece swapped in from the queue
+        assert_eq!(game.active_piece().kind(), PieceKind::L);
+        assert_eq!(game.active_piece().orientation(), Orientation::Right);
+        assert_eq!(game.held_piece().map(PieceData::kind), Some(PieceKind::J));
+    }
+
+    #[test]
+    fn inputs_during_are_are_dropped_without_buffering() {
+        let mut game = game_with_sequence(&[PieceKind::T, PieceKind::J]);
+        game.set_are(0.5);
+        game.hard_drop();
+
+        assert!(!game.handle_input(GameInput::RotateCW));
+        game.tick(0.5);
+        assert_eq!(game.active_piece().orientation(), Orientation::Spawn);
+    }
+
+    #[test]
+    fn first_active_piece_is_a_real_tetromino() {
+        for seed in 0..8 {
+            let game = Game::with_seed(seed);
+            let piece = game.active_piece();
+            assert!(PieceKind::ALL.contains(&piece.kind()));
+            assert_eq!(game.active_piece_cells().len(), 4);
+            assert!(!game.playfield().has_overlap(piece));
+            assert_eq!(game.piece_counts().iter().sum::<u32>(), 1);
+        }
+    }
+
+    #[test]
+    fn spawned_t_cells() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+
+        let mut cells = game.active_piece_cells();
+        cells.sort();
+        assert_eq!(cells, [Vec2I8::new(3, 21), Vec2I8::new(4, 20), Vec2I8::new(4, 21), Vec2I8::new(5, 21)]);
+    }
+
+    #[test]
+    fn index_writes_read_back() {
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::O);
+        game.hard_drop();
+
+        let board = game.playfield_mut();
+        assert_eq!(board.cell_kind(4, 39), CellKind::Piece(PieceKind::O));
+
+        board[(4, 39)] = Color::WHITE;
+        board[(0, 0)] = Color::WHITE;
+        assert_eq!(board[(4, 39)], Color::WHITE);
+        assert_eq!(board[(0, 0)], Color::WHITE);
+        assert_eq!(board.get_tile(0, 0), Color::WHITE);
+
+        // Writing through the index turns the tile into garbage
+        assert_eq!(board.cell_kind(4, 39), CellKind::Garbage);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_out_of_range_panics() {
+        let board = Playfield::new();
+        let _ = board[(10, 0)];
+    }
+
+    #[test]
+    fn filled_cells_after_two_locks() {
+        let mut game = game_with_sequence(&[PieceKind::O, PieceKind::T]);
+        game.hard_drop();
+        game.hard_drop();
+
+        let board = game.playfield();
+        assert_eq!(board.filled_cells().count(), 8);
+        assert_eq!(board.iter().count(), 10 * 40);
+
+        // Tiles are visited row by row from the top, so the T on top of the O comes first
+        let first = board.filled_cells().next().unwrap();
+        assert_eq!((first.0, first.1), (4, 36));
+        assert_eq!(first.2, PieceData::create_all_pieces()[PieceKind::T.index()].color());
+    }
+
+    #[test]
+    fn clear_result_reports_cleared_rows() {
+        let mut game = game_with_board("
+            .########.
+            .#########
+            .########.
+            .#########
+        ");
+        game.force_active_piece(PieceKind::I);
+        game.rotate_right();
+        while game.move_left() {}
+
+        let result = game.hard_drop().unwrap();
+        assert_eq!(result.lines, 2);
+        assert_eq!(result.cleared_rows, [17, 19]);
+
+        // The rows left over moved down into the cleared ones
+        assert!(game.playfield().occupancy_eq(&Playfield::from_ascii("
+            #########.
+            #########.
+        ").unwrap()));
+    }
+
+    #[test]
+    fn steps_to_lock_in_both_lock_modes() {
+        let mut game = Game::with_seed(0);
+        game.set_gravity(2.0);
+        game.set_lock_delay(0.5);
+        let distance = game.drop_distance();
+
+        // Half a second of lock delay at 2 cells per second is one more gravity step
+        assert_eq!(game.steps_to_lock(), distance + 1);
+
+        game.set_lock_mode(LockMode::Classic);
+        assert_eq!(game.steps_to_lock(), distance + 1);
+        game.sonic_drop();
+        assert_eq!(game.steps_to_lock(), 0);
+    }
+
+    #[test]
+    fn steps_to_lock_with_infinite_gravity() {
+        let mut game = Game::with_seed(0);
+        game.set_gravity(f64::INFINITY);
+        assert!(game.drop_distance() > 0);
+
+        // The drop is instant, so only the remaining lock delay counts
+        assert_eq!(game.steps_to_lock(), 1);
+        game.set_lock_delay(0.0);
+        assert_eq!(game.steps_to_lock(), 0);
+
+        game.set_lock_mode(LockMode::Classic);
+        assert_eq!(game.steps_to_lock(), 1);
+    }
+
+    #[test]
+    fn steps_to_lock_without_gravity() {
+        let mut game = Game::with_seed(0);
+        game.set_gravity(0.0);
+
+        // A floating piece never falls, so it never locks
+        assert_eq!(game.steps_to_lock(), usize::MAX);
+
+        // A resting piece still locks once the lock delay runs out
+        game.sonic_drop();
+        assert_eq!(game.steps_to_lock(), 1);
+        game.set_lock_delay(0.0);
+        assert_eq!(game.steps_to_lock(), 0);
     }
 }
@@ -0,0 +1,42 @@
+//! Defines the player inputs a game understands.
+
+/// An action of the player, applied with [`Game::handle_input()`].
+///
+/// [`Game::handle_input()`]: crate::game::Game::handle_input
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameInput {
+    /// Moves the active piece one column left.
+    MoveLeft,
+
+    /// Moves the active piece one column right.
+    MoveRight,
+
+    /// Moves the active piece one row down, awarding soft-drop points.
+    SoftDrop,
+
+    /// Drops the active piece to the bottom and locks it.
+    HardDrop,
+
+    /// Rotates the active piece clockwise.
+    RotateCW,
+
+    /// Rotates the active piece counter-clockwise.
+    RotateCCW,
+
+    /// Rotates the active piece by 180°.
+    Rotate180,
+
+    /// Swaps the active piece with the hold slot.
+    Hold
+}
+
+/// A horizontal direction the player can hold for auto-shifting with [`Game::hold_direction()`].
+///
+/// [`Game::hold_direction()`]: crate::game::Game::hold_direction
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HorizontalDir {
+    Left,
+    Right
+}
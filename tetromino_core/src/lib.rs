@@ -1,10 +1,38 @@
 //! Holds the core logic and data structures to create a tetromino game.
 //! It's... just a tetris clone.
+//!
+//! With the default `std` feature disabled, this crate is `no_std` and only needs `alloc`.
+//! Everything except for entropy-seeded construction remains available, so games are created
+//! with [`Game::with_seed()`] or [`Game::with_randomizer()`] instead of `Game::new()`.
+//! The `std::error::Error` impls of the error types are not available either.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod pieces;
 pub mod game;
 pub mod misc;
+pub mod event;
+pub mod randomizer;
+pub mod score;
+pub mod input;
+pub mod replay;
+pub mod palette;
+pub mod ai;
+pub mod builder;
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 pub use pieces::*;
 pub use game::*;
 pub use misc::*;
+pub use event::*;
+pub use randomizer::*;
+pub use score::*;
+pub use input::*;
+pub use replay::*;
+pub use palette::*;
+pub use ai::*;
+pub use builder::*;
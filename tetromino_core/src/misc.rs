@@ -1,10 +1,15 @@
 //! Holds misc. types used throughout this library.
 
-use std::ops::*;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::*;
+use core::str::FromStr;
 
 /// Defines a 2D Vector used to represent points and directions.
 /// This supplies blanket implementations based on its parameter.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2<T> {
     pub x: T,
     pub y: T
@@ -22,11 +27,47 @@ pub struct Color {
     pub b: u8
 }
 
+/// The reason a hex string could not be parsed by [`Color::from_hex()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorParseError {
+    /// The string does not have exactly 6 hex digits. Holds the amount of characters found instead.
+    InvalidLength(usize),
+
+    /// The string contains a character that is not a hex digit.
+    InvalidDigit(char)
+}
+
 impl<T> Vec2<T> {
     /// Creates a new vector with x and y components.
     pub const fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+
+    /// Applies a function to both components, creating a new vector from the results.
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Vec2<U> {
+        Vec2::new(f(self.x), f(self.y))
+    }
+
+    /// Combines the components of two vectors pair-wise with a function.
+    pub fn zip_with<U, Out>(self, other: Vec2<U>, mut f: impl FnMut(T, U) -> Out) -> Vec2<Out> {
+        Vec2::new(f(self.x, other.x), f(self.y, other.y))
+    }
+
+    /// Converts both components to another type using its [`From`] conversion.
+    /// Only lossless conversions are available this way, so signs and values are always kept.
+    pub fn cast<U: From<T>>(self) -> Vec2<U> {
+        self.map(U::from)
+    }
+
+    /// Converts both components to [`f64`] using its [`From`] conversion.
+    pub fn as_f64(self) -> Vec2<f64> where f64: From<T> {
+        self.cast()
+    }
+
+    /// Calculates the dot product of two vectors.
+    pub fn dot<Rhs, Out: Add<Output = Out>>(self, rhs: Vec2<Rhs>) -> Out where T: Mul<Rhs, Output = Out> {
+        self.x * rhs.x + self.y * rhs.y
+    }
 }
 
 impl<T: Add<Rhs, Output = Out>, Rhs, Out> Add<Vec2<Rhs>> for Vec2<T>  {
@@ -59,6 +100,21 @@ impl<T: SubAssign<Rhs>, Rhs> SubAssign<Vec2<Rhs>> for Vec2<T> {
     }
 }
 
+impl<T: Mul<Rhs, Output = Out>, Rhs: Copy, Out> Mul<Rhs> for Vec2<T> {
+    type Output = Vec2<Out>;
+
+    fn mul(self, rhs: Rhs) -> Self::Output {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: MulAssign<Rhs>, Rhs: Copy> MulAssign<Rhs> for Vec2<T> {
+    fn mul_assign(&mut self, rhs: Rhs) {
+        self.x *= rhs;
+        self.y *= rhs;
+    }
+}
+
 impl<T: Neg<Output = Out>, Out> Neg for Vec2<T> {
     type Output = Vec2<Out>;
 
@@ -100,12 +156,91 @@ impl Color {
         Color { r, g, b }
     }
 
+    /// Creates a new color from its hue in degrees, and its saturation and value in the range [0, 1].
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        // Float math functions need std, so the euclidean remainder and absolute value are done by hand
+        let hue = hue % 360.0;
+        let sector = if hue < 0.0 { hue + 360.0 } else { hue } / 60.0;
+        let chroma = value * saturation;
+        let distance = sector % 2.0 - 1.0;
+        let x = chroma * (1.0 - if distance < 0.0 { -distance } else { distance });
+        let m = value - chroma;
+
+        let (r, g, b) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x)
+        };
+
+        let to_u8 = |c: f32| round_to_u8((c + m) * 255.0);
+        Color::new(to_u8(r), to_u8(g), to_u8(b))
+    }
+
+    /// Parses a color from a hex string like `"#00f0f0"`. The `#` is optional and case is ignored.
+    pub fn from_hex(text: &str) -> Result<Color, ColorParseError> {
+        let hex = text.strip_prefix('#').unwrap_or(text);
+        if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ColorParseError::InvalidDigit(c));
+        }
+
+        if hex.len() != 6 {
+            return Err(ColorParseError::InvalidLength(hex.len()));
+        }
+
+        // Only hex digits are left, so this cannot fail
+        let value = u32::from_str_radix(hex, 16).unwrap();
+        Ok(Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8))
+    }
+
+    /// Formats the color as a lowercase hex string like `"#00f0f0"`.
+    pub fn to_hex(&self) -> String {
+        self.to_string()
+    }
+
+    /// Multiplies each channel by a factor, clamping the results to the range [0, 255].
+    pub fn scaled(self, factor: f32) -> Color {
+        let scale = |c: u8| round_to_u8(c as f32 * factor);
+        Color::new(scale(self.r), scale(self.g), scale(self.b))
+    }
+
+    /// Linearly interpolates between this color at `t = 0` and `other` at `t = 1`.
+    /// Values of `t` outside [0, 1] extrapolate, with the channels clamped to the range [0, 255].
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let mix = |a: u8, b: u8| round_to_u8(a as f32 + (b as f32 - a as f32) * t);
+        Color::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    /// Gets the color with every channel inverted.
+    pub fn inverted(self) -> Color {
+        Color::new(255 - self.r, 255 - self.g, 255 - self.b)
+    }
+
+    /// Gets the perceived brightness of the color, weighting the channels by 0.299, 0.587 and 0.114.
+    pub fn luminance(&self) -> u8 {
+        round_to_u8(0.299 * self.r as f32 + 0.587 * self.g as f32 + 0.114 * self.b as f32)
+    }
+
+    /// Gets the gray color with the same [`Color::luminance()`].
+    pub fn to_grayscale(&self) -> Color {
+        let l = self.luminance();
+        Color::new(l, l, l)
+    }
+
     /// Determines if the present color is pure black.
     pub fn is_black(&self) -> bool {
         self.r == 0 && self.g == 0 && self.b == 0
     }
 }
 
+/// Rounds a channel value to the nearest integer, clamped to the range [0, 255].
+fn round_to_u8(value: f32) -> u8 {
+    // Float to int casts truncate towards zero and saturate, so this cannot overflow and needs no std
+    (value + 0.5) as u8
+}
+
 impl Default for Color {
     fn default() -> Self { Color::BLACK }
 }
@@ -115,3 +250,124 @@ impl PartialEq for Color {
         self.r == other.r && self.g == other.g && self.b == other.b
     }
 }
+
+impl Hash for Color {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.r.hash(state);
+        self.g.hash(state);
+        self.b.hash(state);
+    }
+}
+
+/// Formats the color as a lowercase hex string like `#00f0f0`.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::from_hex(s)
+    }
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorParseError::InvalidLength(len) => write!(f, "expected 6 hex digits, found {}", len),
+            ColorParseError::InvalidDigit(c) => write!(f, "invalid hex digit {:?}", c)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ColorParseError {}
+
+/// Serializes colors compactly as a hex string like `"#00f0f0"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes colors from a hex string like `"#00f0f0"`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use alloc::format;
+        use serde::de::Error;
+
+        let text = String::deserialize(deserializer)?;
+        Color::from_hex(&text).map_err(|e| D::Error::custom(format!("invalid color {:?}: {}", text, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec2_scales_and_maps() {
+        assert_eq!(Vec2I8::new(1, -2) * 3, Vec2I8::new(3, -6));
+
+        let mut v = Vec2I8::new(2, 5);
+        v *= 3;
+        assert_eq!(v, Vec2I8::new(6, 15));
+
+        let f: Vec2<f64> = Vec2I8::new(1, -2).map(|c| c as f64 * 0.5);
+        assert_eq!(f, Vec2::new(0.5, -1.0));
+    }
+
+    #[test]
+    fn negative_vec2_widens_with_sign() {
+        let v = Vec2I8::new(-3, -128);
+        assert_eq!(v.cast::<i32>(), Vec2::new(-3i32, -128));
+        assert_eq!(v.as_f64(), Vec2::new(-3.0, -128.0));
+    }
+
+    #[test]
+    fn color_hex_round_trip() {
+        let cyan = Color::new(0x00, 0xf0, 0xf0);
+        assert_eq!(Color::from_hex("#00F0F0"), Ok(cyan));
+        assert_eq!(Color::from_hex("00f0f0"), Ok(cyan));
+        assert_eq!("#00f0f0".parse(), Ok(cyan));
+        assert_eq!(cyan.to_hex(), "#00f0f0");
+    }
+
+    #[test]
+    fn invalid_hex_is_rejected() {
+        assert_eq!(Color::from_hex("#xyz"), Err(ColorParseError::InvalidDigit('x')));
+        assert_eq!(Color::from_hex("#0f0"), Err(ColorParseError::InvalidLength(3)));
+        assert_eq!(Color::from_hex(""), Err(ColorParseError::InvalidLength(0)));
+    }
+
+    #[test]
+    fn scaling_clamps_at_white() {
+        assert_eq!(Color::new(200, 100, 0).scaled(2.0), Color::new(255, 200, 0));
+        assert_eq!(Color::new(200, 100, 0).scaled(0.5), Color::new(100, 50, 0));
+    }
+
+    #[test]
+    fn lerp_midpoint_and_clamping() {
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 0.5), Color::new(128, 128, 128));
+        assert_eq!(Color::new(0, 100, 200).lerp(Color::new(100, 100, 0), 0.5), Color::new(50, 100, 100));
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 2.0), Color::WHITE);
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, -1.0), Color::BLACK);
+    }
+
+    #[test]
+    fn luminance_and_grayscale() {
+        let cyan = Color::new(0x00, 0xf0, 0xf0);
+        assert_eq!(cyan.luminance(), 168);
+
+        let gray = cyan.to_grayscale();
+        assert_eq!((gray.r, gray.g, gray.b), (168, 168, 168));
+
+        assert_eq!(Color::WHITE.luminance(), 255);
+        assert_eq!(Color::BLACK.luminance(), 0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Defines which colors the pieces are displayed in.
+
+use crate::misc::Color;
+use crate::pieces::{PieceKind, PIECE_COUNT};
+
+/// Maps every kind of piece to the color it is displayed in.
+///
+/// Apply one to a game with [`Game::set_palette()`]. Renderers then consult it to color the pieces,
+/// while the game only keeps track of their kinds.
+///
+/// Black is not a valid piece color, since it marks empty tiles.
+///
+/// [`Game::set_palette()`]: crate::game::Game::set_palette
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    colors: [Color; PIECE_COUNT]
+}
+
+impl Palette {
+    /// Creates a palette from the colors of the pieces, in the order of [`PieceKind::ALL`].
+    ///
+    /// Panics if any of the colors is black.
+    pub const fn new(colors: [Color; PIECE_COUNT]) -> Self {
+        let mut i = 0;
+        while i < PIECE_COUNT {
+            let c = colors[i];
+            assert!(c.r != 0 || c.g != 0 || c.b != 0, "black is not a valid piece color");
+            i += 1;
+        }
+
+        Palette { colors }
+    }
+
+    /// Creates the palette with the guideline colors, which the pieces have by default.
+    pub const fn guideline() -> Self {
+        Palette::new([
+            Color::new(0x00, 0xf0, 0xf0),
+            Color::new(0x00, 0x00, 0xf0),
+            Color::new(0xf0, 0xa0, 0x00),
+            Color::new(0xf0, 0xf0, 0x00),
+            Color::new(0x00, 0xf0, 0x00),
+            Color::new(0xa0, 0x00, 0xf0),
+            Color::new(0xf0, 0x00, 0x00)
+        ])
+    }
+
+    /// Creates a palette that displays every piece in the same light gray.
+    pub const fn monochrome() -> Self {
+        Palette::new([Color::new(0xd0, 0xd0, 0xd0); PIECE_COUNT])
+    }
+
+    /// Creates a palette with softer versions of the guideline colors.
+    pub const fn pastel() -> Self {
+        Palette::new([
+            Color::new(0x80, 0xf8, 0xf8),
+            Color::new(0x80, 0x80, 0xf8),
+            Color::new(0xf8, 0xd0, 0x80),
+            Color::new(0xf8, 0xf8, 0x80),
+            Color::new(0x80, 0xf8, 0x80),
+            Color::new(0xd0, 0x80, 0xf8),
+            Color::new(0xf8, 0x80, 0x80)
+        ])
+    }
+
+    /// Gets the color of a kind of piece.
+    pub const fn color(&self, kind: PieceKind) -> Color {
+        self.colors[kind.index()]
+    }
+
+    /// Sets the color of a kind of piece.
+    ///
+    /// Panics if the color is black.
+    pub fn set_color(&mut self, kind: PieceKind, color: Color) {
+        assert!(!color.is_black(), "black is not a valid piece color");
+        self.colors[kind.index()] = color;
+    }
+
+    /// Gets the colors of all pieces, in the order of [`PieceKind::ALL`].
+    pub fn colors(&self) -> &[Color; PIECE_COUNT] {
+        &self.colors
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::guideline()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CellKind, Game};
+
+    #[test]
+    fn custom_palette_changes_t_color_but_not_kind() {
+        let pink = Color::new(0xff, 0x80, 0xc0);
+        let mut palette = Palette::guideline();
+        palette.set_color(PieceKind::T, pink);
+
+        let mut game = Game::with_seed(0);
+        game.force_active_piece(PieceKind::T);
+        game.hard_drop();
+        game.set_palette(palette);
+
+        let playfield = game.playfield();
+        let bottom = playfield.true_height() - 1;
+        let x = (0..playfield.width())
+            .find(|&x| playfield.cell_kind(x, bottom) == CellKind::Piece(PieceKind::T))
+            .unwrap();
+        assert_eq!(playfield.tile_color(x, bottom, game.palette()), pink);
+
+        game.force_active_piece(PieceKind::T);
+        let piece = &game.active_piece().piece_data;
+        assert_eq!(piece.kind(), PieceKind::T);
+        assert_eq!(game.piece_color(piece), pink);
+        assert_eq!(game.piece_color(&game.next_pieces()[0]), Palette::guideline().color(game.next_pieces()[0].kind()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn black_is_rejected() {
+        let mut palette = Palette::pastel();
+        palette.set_color(PieceKind::I, Color::BLACK);
+    }
+}
@@ -0,0 +1,152 @@
+//! Defines the kick tests pieces perform when rotating.
+
+use crate::misc::Vec2I8;
+
+/// Defines the kick tests to check when rotating right out of each of the 4 states of a piece.
+/// Rotating left uses the negated tests of the target state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KickTable {
+    tests: [[Vec2I8; 4]; 4]
+}
+
+impl KickTable {
+    /// Creates a kick table from the tests for rotating right out of each state.
+    /// The `(0, 0)` check is implied and should not be included.
+    pub const fn new(tests: [[Vec2I8; 4]; 4]) -> Self {
+        KickTable { tests }
+    }
+
+    /// Creates the SRS kick tests of the J, L, S, T, and Z pieces.
+    pub const fn srs_jlstz() -> Self {
+        KickTable::new([
+            [
+                Vec2I8::new(-1, 0),
+                Vec2I8::new(-1, -1),
+                Vec2I8::new(0, 2),
+                Vec2I8::new(-1, 2)
+            ],
+            [
+                Vec2I8::new(1, 0),
+                Vec2I8::new(1, 1),
+                Vec2I8::new(0, -2),
+                Vec2I8::new(1, -2)
+            ],
+            [
+                Vec2I8::new(1, 0),
+                Vec2I8::new(1, -1),
+                Vec2I8::new(0, 2),
+                Vec2I8::new(1, 2)
+            ],
+            [
+                Vec2I8::new(-1, 0),
+                Vec2I8::new(-1, 1),
+                Vec2I8::new(0, -2),
+                Vec2I8::new(-1, -2)
+            ]
+        ])
+    }
+
+    /// Creates the SRS kick tests of the I piece.
+    pub const fn srs_i() -> Self {
+        KickTable::new([
+            [
+                Vec2I8::new(-2, 0),
+                Vec2I8::new(1, 0),
+                Vec2I8::new(-2, 1),
+                Vec2I8::new(1, -2)
+            ],
+            [
+                Vec2I8::new(-1, 0),
+                Vec2I8::new(2, 0),
+                Vec2I8::new(-1, -2),
+                Vec2I8::new(2, 1)
+            ],
+            [
+                Vec2I8::new(2, 0),
+                Vec2I8::new(-1, 0),
+                Vec2I8::new(2, -1),
+                Vec2I8::new(-1, 2)
+            ],
+            [
+                Vec2I8::new(1, 0),
+                Vec2I8::new(-2, 0),
+                Vec2I8::new(1, 2),
+                Vec2I8::new(-2, -1)
+            ]
+        ])
+    }
+
+    /// Creates a kick table that never kicks, so rotations only succeed in place.
+    pub const fn none() -> Self {
+        KickTable::new([[Vec2I8::new(0, 0); 4]; 4])
+    }
+
+    /// Gets the kick tests for rotating right out of each state.
+    pub const fn tests(&self) -> &[[Vec2I8; 4]; 4] {
+        &self.tests
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the tests for rotating from each state to the states right and left of it,
+    /// as they are applied by the game. Left rotations use the negated tests of the target state.
+    fn applied_tests(table: &KickTable) -> [[[i8; 2]; 4]; 8] {
+        let mut applied = [[[0; 2]; 4]; 8];
+        for from in 0..4 {
+            let left = (from + 3) % 4;
+            let tests = table.tests()[from].iter().zip(&table.tests()[left]);
+            for (i, (right_test, left_test)) in tests.enumerate() {
+                applied[from * 2][i] = [right_test.x, right_test.y];
+                applied[from * 2 + 1][i] = [-left_test.x, -left_test.y];
+            }
+        }
+
+        applied
+    }
+
+    /// Converts a guideline table, whose y axis points up, to the y axis pointing down.
+    fn flip_y(mut guideline: [[[i8; 2]; 4]; 8]) -> [[[i8; 2]; 4]; 8] {
+        for test in guideline.iter_mut().flatten() {
+            test[1] = -test[1];
+        }
+
+        guideline
+    }
+
+    #[test]
+    fn jlstz_kicks_match_guideline() {
+        // 0->R, 0->L, R->2, R->0, 2->L, 2->R, L->0, L->2
+        let guideline = [
+            [[-1, 0], [-1, 1], [0, -2], [-1, -2]],
+            [[1, 0], [1, 1], [0, -2], [1, -2]],
+            [[1, 0], [1, -1], [0, 2], [1, 2]],
+            [[1, 0], [1, -1], [0, 2], [1, 2]],
+            [[1, 0], [1, 1], [0, -2], [1, -2]],
+            [[-1, 0], [-1, 1], [0, -2], [-1, -2]],
+            [[-1, 0], [-1, -1], [0, 2], [-1, 2]],
+            [[-1, 0], [-1, -1], [0, 2], [-1, 2]]
+        ];
+
+        assert_eq!(applied_tests(&KickTable::srs_jlstz()), flip_y(guideline));
+    }
+
+    #[test]
+    fn i_kicks_match_guideline() {
+        // 0->R, 0->L, R->2, R->0, 2->L, 2->R, L->0, L->2
+        let guideline = [
+            [[-2, 0], [1, 0], [-2, -1], [1, 2]],
+            [[-1, 0], [2, 0], [-1, 2], [2, -1]],
+            [[-1, 0], [2, 0], [-1, 2], [2, -1]],
+            [[2, 0], [-1, 0], [2, 1], [-1, -2]],
+            [[2, 0], [-1, 0], [2, 1], [-1, -2]],
+            [[1, 0], [-2, 0], [1, -2], [-2, 1]],
+            [[1, 0], [-2, 0], [1, -2], [-2, 1]],
+            [[-2, 0], [1, 0], [-2, -1], [1, 2]]
+        ];
+
+        assert_eq!(applied_tests(&KickTable::srs_i()), flip_y(guideline));
+    }
+}
@@ -2,13 +2,18 @@
 
 use crate::misc::{Color, Vec2I8};
 
+use core::fmt;
+
 mod pieces_def;
+mod kick_table;
+
+pub use kick_table::KickTable;
 
 /// Defines the bool matrix for a piece.
 pub type PieceBoolMatrix = [[bool; 4]; 4];
 
 /// Defines the matrix for a given piece state.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PieceMatrix {
     bits: u16,
     size: u8
@@ -23,15 +28,44 @@ pub struct PieceState {
     kick_tests: [Vec2I8; 4]
 }
 
+/// Identifies which of the pieces a [`PieceData`] represents.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PieceKind {
+    I,
+    J,
+    L,
+    O,
+    S,
+    T,
+    Z
+}
+
+/// Names the rotation states of a piece, in the order rotating right cycles through them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    /// The state a piece spawns in.
+    Spawn,
+    /// One rotation right of the spawn state.
+    Right,
+    /// Two rotations away from the spawn state.
+    Flip,
+    /// One rotation left of the spawn state.
+    Left
+}
+
 /// Defines data needed to represent a piece.
 #[derive(Clone)]
 pub struct PieceData {
     states: [PieceState; 4],
-    color: Color
+    color: Color,
+    kind: PieceKind,
+    spawn_offset: Vec2I8
 }
 
 /// The amount of unique pieces that exist.
-pub(crate) const PIECE_COUNT: usize = 7;
+pub const PIECE_COUNT: usize = 7;
 
 /// Converts the bits of a [PieceMatrix] to an actual 4x4 bool matrix.
 const fn bits_to_matrix(bits: u16) -> PieceBoolMatrix {
@@ -43,22 +77,56 @@ const fn bits_to_matrix(bits: u16) -> PieceBoolMatrix {
     ]
 }
 
+/// Computes where a piece spawns relative to the center column and the top visible row of the playfield.
+///
+/// The filled blocks of the default state are centered, rounding to the left, and their top row
+/// is put into the top visible row.
+const fn compute_spawn_offset(base: &PieceMatrix) -> Vec2I8 {
+    let mat = bits_to_matrix(base.bits);
+    let mut min_x = 4;
+    let mut max_x = -1;
+    let mut min_y = 4;
+
+    let mut x = 0;
+    while x < 4 {
+        let mut y = 0;
+        while y < 4 {
+            if mat[x][y] {
+                if (x as i8) < min_x { min_x = x as i8; }
+                if (x as i8) > max_x { max_x = x as i8; }
+                if (y as i8) < min_y { min_y = y as i8; }
+            }
+
+            y += 1;
+        }
+
+        x += 1;
+    }
+
+    if max_x < 0 {
+        return Vec2I8::new(0, 0);
+    }
+
+    let block_width = max_x - min_x + 1;
+    Vec2I8::new(-min_x - (block_width + 1) / 2, -min_y)
+}
+
 /// Converts a 4x4 bool matrix to the corresponding bits for a [PieceMatrix]. 
 const fn matrix_to_bits(mat: &PieceBoolMatrix) -> u16 {
     const fn get_bit(mat: &PieceBoolMatrix, x: u8, y: u8) -> u16 {
         (mat[x as usize][y as usize] as u16) << (x * 4 + y)
     }
 
-    get_bit(&mat, 0, 0) | get_bit(&mat, 0, 1) | get_bit(&mat, 0, 2) | get_bit(&mat, 0, 3) |
-    get_bit(&mat, 1, 0) | get_bit(&mat, 1, 1) | get_bit(&mat, 1, 2) | get_bit(&mat, 1, 3) |
-    get_bit(&mat, 2, 0) | get_bit(&mat, 2, 1) | get_bit(&mat, 2, 2) | get_bit(&mat, 2, 3) |
-    get_bit(&mat, 3, 0) | get_bit(&mat, 3, 1) | get_bit(&mat, 3, 2) | get_bit(&mat, 3, 3)
+    get_bit(mat, 0, 0) | get_bit(mat, 0, 1) | get_bit(mat, 0, 2) | get_bit(mat, 0, 3) |
+    get_bit(mat, 1, 0) | get_bit(mat, 1, 1) | get_bit(mat, 1, 2) | get_bit(mat, 1, 3) |
+    get_bit(mat, 2, 0) | get_bit(mat, 2, 1) | get_bit(mat, 2, 2) | get_bit(mat, 2, 3) |
+    get_bit(mat, 3, 0) | get_bit(mat, 3, 1) | get_bit(mat, 3, 2) | get_bit(mat, 3, 3)
 }
 
 impl PieceData {
     /// Creates a new piece, based on its default rotational matrix,
     /// the kick tests to perform when rotating, and the color to display it as.
-    const fn new(base: PieceMatrix, kick_tests: &[[Vec2I8; 4]; 4], color: Color) -> PieceData {
+    const fn new(kind: PieceKind, base: PieceMatrix, kick_tests: &[[Vec2I8; 4]; 4], color: Color) -> PieceData {
         let mut states = [PieceState::empty(); 4];
         
         // Macro to deduplicate code from loop-unrolling due to const-ness
@@ -78,11 +146,58 @@ impl PieceData {
 
         PieceData {
             states,
-            color
+            color,
+            kind,
+            spawn_offset: compute_spawn_offset(&base)
         }
     }
 
-    /// Creates an array of all possible pieces.
+    /// Creates a custom piece from the filled blocks of its default rotational matrix.
+    /// The other rotation states are derived by rotating it right.
+    ///
+    /// The blocks are rotated within the smallest square containing them and `(0, 0)`, but at least 2x2.
+    /// The kind determines how the piece is counted and whether it is checked for T-spins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cells` is empty or any of the blocks lies outside of the 4x4 matrix.
+    pub fn from_cells(kind: PieceKind, cells: &[Vec2I8], color: Color, kick: KickTable) -> PieceData {
+        assert!(!cells.is_empty(), "a piece needs at least one block");
+
+        let mut mat = [[false; 4]; 4];
+        for &cell in cells {
+            assert!((0..4).contains(&cell.x) && (0..4).contains(&cell.y), "block {:?} does not fit into a 4x4 matrix", cell);
+            mat[cell.x as usize][cell.y as usize] = true;
+        }
+
+        PieceData::new(kind, PieceMatrix::from(mat), kick.tests(), color)
+    }
+
+    /// Creates a copy of this piece that is mirrored left-to-right, with all rotation states derived anew.
+    ///
+    /// The kind is mirrored as well, so S and Z as well as J and L swap. The color and kick tests are kept.
+    pub fn mirrored(&self) -> PieceData {
+        let base = self.states[0].matrix;
+        let size = base.size as usize;
+        let mat = base.matrix();
+
+        let mut mirrored = [[false; 4]; 4];
+        for x in 0..size {
+            mirrored[x] = mat[size - 1 - x];
+        }
+
+        let base = PieceMatrix { bits: matrix_to_bits(&mirrored), size: base.size };
+        let kick_tests = [
+            self.states[0].kick_tests,
+            self.states[1].kick_tests,
+            self.states[2].kick_tests,
+            self.states[3].kick_tests
+        ];
+
+        PieceData::new(self.kind.mirrored(), base, &kick_tests, self.color)
+    }
+
+    /// Creates an array of all possible pieces, in the order of [`PieceKind::ALL`].
     pub const fn create_all_pieces() -> [PieceData; PIECE_COUNT] {
         pieces_def::create_all_pieces()
     }
@@ -102,6 +217,17 @@ impl PieceData {
         self.color
     }
 
+    /// Gets which piece this is.
+    pub fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    /// Gets the position of the piece's matrix when it spawns, relative to the center column
+    /// (rounded to the right) and the top visible row of the playfield.
+    pub fn spawn_offset(&self) -> Vec2I8 {
+        self.spawn_offset
+    }
+
     /// Gets the size of the piece.
     pub fn size(&self) -> u8 {
         self.states[0].matrix.size
@@ -113,11 +239,90 @@ impl PieceData {
     }
 }
 
+impl PieceKind {
+    /// All kinds of pieces, in the order [`PieceData::create_all_pieces()`] returns them.
+    pub const ALL: [PieceKind; PIECE_COUNT] = [
+        PieceKind::I,
+        PieceKind::J,
+        PieceKind::L,
+        PieceKind::O,
+        PieceKind::S,
+        PieceKind::T,
+        PieceKind::Z
+    ];
+
+    /// Gets the index of this kind in [`PieceKind::ALL`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Gets the kind of piece this one turns into when mirrored left-to-right.
+    pub const fn mirrored(self) -> PieceKind {
+        match self {
+            PieceKind::J => PieceKind::L,
+            PieceKind::L => PieceKind::J,
+            PieceKind::S => PieceKind::Z,
+            PieceKind::Z => PieceKind::S,
+            other => other
+        }
+    }
+
+    /// Gets the letter the piece is named after.
+    pub const fn letter(self) -> char {
+        match self {
+            PieceKind::I => 'I',
+            PieceKind::J => 'J',
+            PieceKind::L => 'L',
+            PieceKind::O => 'O',
+            PieceKind::S => 'S',
+            PieceKind::T => 'T',
+            PieceKind::Z => 'Z'
+        }
+    }
+}
+
+impl Orientation {
+    /// All orientations, ordered by their rotation index.
+    pub const ALL: [Orientation; 4] = [
+        Orientation::Spawn,
+        Orientation::Right,
+        Orientation::Flip,
+        Orientation::Left
+    ];
+
+    /// Gets the rotation index of this orientation, as used by [`PieceData::state()`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Gets the orientation with the given rotation index, or `None` if it is not in [0..=3].
+    pub const fn from_index(index: usize) -> Option<Orientation> {
+        if index < 4 {
+            Some(Orientation::ALL[index])
+        } else {
+            None
+        }
+    }
+}
+
+/// Formats the kind as the letter the piece is named after.
+impl fmt::Display for PieceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+/// Creates a placeholder that is not a playable piece.
+///
+/// Every state fills the whole 4x4 matrix and has no kick tests. The kind is meaningless.
+/// Use [`PieceData::create_all_pieces()`] to get the actual pieces.
 impl Default for PieceData {
     fn default() -> Self {
         Self { 
             states: [PieceState::default(); 4],
-            color: Color::BLACK
+            color: Color::BLACK,
+            kind: PieceKind::I,
+            spawn_offset: Vec2I8::new(0, 0)
         }
     }
 }
@@ -160,7 +365,7 @@ impl PieceMatrix {
     /// Creates a new size 4 matrix from the given filled blocks.
     const fn new_size4(bits: &[[bool; 4]; 4]) -> Self {
         PieceMatrix {
-            bits: matrix_to_bits(&bits),
+            bits: matrix_to_bits(bits),
             size: 4
         }
     }
@@ -173,7 +378,7 @@ impl PieceMatrix {
             let b = bits_to_matrix(s.bits);
             PieceMatrix::new_size2(&[
                 [b[0][1], b[1][1]],
-                [b[0][0], b[0][1]],
+                [b[0][0], b[1][0]],
             ])
         }
 
@@ -197,17 +402,84 @@ impl PieceMatrix {
         }
 
         match self.size {
-            2 => rot2(&self),
-            3 => rot3(&self),
-            4 => rot4(&self),
+            2 => rot2(self),
+            3 => rot3(self),
+            4 => rot4(self),
             _ => *self
         }
     }
 
+    /// Creates a matrix from the bits returned by [`PieceMatrix::to_bits()`] and its size.
+    ///
+    /// Returns [`None`] if the size is not 2, 3, or 4, or any bit outside of the size is set.
+    pub fn from_bits(bits: u16, size: u8) -> Option<Self> {
+        if !(2..=4).contains(&size) {
+            return None;
+        }
+
+        let matrix = PieceMatrix { bits, size };
+        if matrix.iter_cells().count() as u32 == matrix.cell_count() {
+            Some(matrix)
+        } else {
+            None
+        }
+    }
+
+    /// Creates a matrix of the given size from a bool matrix, indexed as `mat[x][y]`.
+    ///
+    /// Returns [`None`] if the size is not 2, 3, or 4, or any block outside of the size is filled.
+    pub fn from_bool_matrix(mat: &PieceBoolMatrix, size: u8) -> Option<Self> {
+        PieceMatrix::from_bits(matrix_to_bits(mat), size)
+    }
+
+    /// Gets the bits of the filled blocks. Bit `x * 4 + y` is set if the block at `(x, y)` is filled.
+    pub fn to_bits(&self) -> u16 {
+        self.bits
+    }
+
+    /// Gets the size of the square the blocks are rotated in.
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
     /// Gets the 4x4 bool matrix that represents this piece.
     pub fn matrix(&self) -> PieceBoolMatrix {
         bits_to_matrix(self.bits)
     }
+
+    /// Iterates over the local `(x, y)` coordinates of the filled blocks within the matrix size.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (u8, u8)> {
+        let bits = self.bits;
+        let size = self.size;
+        (0..size)
+            .flat_map(move |x| (0..size).map(move |y| (x, y)))
+            .filter(move |&(x, y)| bits & (1 << (x * 4 + y)) != 0)
+    }
+
+    /// Gets the amount of filled blocks.
+    pub fn cell_count(&self) -> u32 {
+        self.bits.count_ones()
+    }
+
+    /// Gets the inclusive minimum and maximum coordinates of the filled blocks.
+    /// An empty matrix returns `(0, 0)` for both.
+    pub fn bounds(&self) -> (Vec2I8, Vec2I8) {
+        let mut min = Vec2I8::new(4, 4);
+        let mut max = Vec2I8::new(-1, -1);
+
+        for (x, y) in self.iter_cells() {
+            min.x = min.x.min(x as i8);
+            min.y = min.y.min(y as i8);
+            max.x = max.x.max(x as i8);
+            max.y = max.y.max(y as i8);
+        }
+
+        if max.x < 0 {
+            (Vec2I8::new(0, 0), Vec2I8::new(0, 0))
+        } else {
+            (min, max)
+        }
+    }
 }
 
 impl PieceState {
@@ -219,17 +491,53 @@ impl PieceState {
         }
     }
 
+    /// Gets the matrix of this state.
+    pub fn piece_matrix(&self) -> &PieceMatrix {
+        &self.matrix
+    }
+
     /// Gets the 4x4 bool matrix that represents this state.
     pub fn matrix(&self) -> PieceBoolMatrix {
         self.matrix.matrix()
     }
 
+    /// Iterates over the local `(x, y)` coordinates of the filled blocks of this state.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (u8, u8)> {
+        self.matrix.iter_cells()
+    }
+
+    /// Gets the amount of filled blocks of this state.
+    pub fn cell_count(&self) -> u32 {
+        self.matrix.cell_count()
+    }
+
+    /// Gets the inclusive minimum and maximum coordinates of the filled blocks of this state.
+    pub fn bounds(&self) -> (Vec2I8, Vec2I8) {
+        self.matrix.bounds()
+    }
+
     /// Gets the kick tests to check. The `(0, 0)` check is implied.
     pub fn kick_tests(&self) -> &[Vec2I8; 4] {
         &self.kick_tests
     }
 }
 
+/// Creates a matrix from a bool matrix, indexed as `mat[x][y]`.
+///
+/// The size is the smallest square containing all filled blocks and `(0, 0)`, but at least 2.
+impl From<PieceBoolMatrix> for PieceMatrix {
+    fn from(mat: PieceBoolMatrix) -> Self {
+        let mut matrix = PieceMatrix { bits: matrix_to_bits(&mat), size: 4 };
+        matrix.size = matrix.iter_cells()
+            .map(|(x, y)| x.max(y) + 1)
+            .max()
+            .unwrap_or(0)
+            .max(2);
+
+        matrix
+    }
+}
+
 impl Default for PieceMatrix {
     fn default() -> Self {
         PieceMatrix {
@@ -247,3 +555,152 @@ impl Default for PieceState {
         }
     }
 }
+
+/// Serializes the piece as its [`PieceKind`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for PieceData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.kind.serialize(serializer)
+    }
+}
+
+/// Deserializes the piece from its [`PieceKind`], as the matching piece of [`PieceData::create_all_pieces()`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PieceData {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let kind = PieceKind::deserialize(deserializer)?;
+        Ok(PieceData::create_all_pieces()[kind.index()].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_pieces_follow_kind_order() {
+        let kinds = PieceData::create_all_pieces().map(|p| p.kind());
+        assert_eq!(kinds, [PieceKind::I, PieceKind::J, PieceKind::L, PieceKind::O, PieceKind::S, PieceKind::T, PieceKind::Z]);
+        assert_eq!(kinds, PieceKind::ALL);
+
+        for kind in PieceKind::ALL {
+            assert_eq!(PieceKind::ALL[kind.index()], kind);
+        }
+    }
+
+    #[test]
+    fn size_2_rotation_cycles_back() {
+        let original = PieceMatrix::new_size2(&[[true, true], [true, false]]);
+
+        // Rotating clockwise moves the missing corner from the bottom right to the bottom left
+        let rotated = original.rotate_right();
+        assert_eq!(rotated, PieceMatrix::new_size2(&[[true, false], [true, true]]));
+
+        let mut matrix = original;
+        for _ in 0..4 {
+            matrix = matrix.rotate_right();
+        }
+
+        assert_eq!(matrix, original);
+    }
+
+    #[test]
+    fn custom_plus_piece_has_matching_rotations() {
+        let cells = [Vec2I8::new(1, 0), Vec2I8::new(0, 1), Vec2I8::new(1, 1), Vec2I8::new(2, 1), Vec2I8::new(1, 2)];
+        let plus = PieceData::from_cells(PieceKind::T, &cells, Color::WHITE, KickTable::srs_jlstz());
+
+        let mut expected = [[false; 4]; 4];
+        for cell in &cells {
+            expected[cell.x as usize][cell.y as usize] = true;
+        }
+
+        assert_eq!(plus.size(), 3);
+        for state in plus.states() {
+            assert_eq!(state.matrix(), expected);
+            assert_eq!(state.cell_count(), 5);
+        }
+    }
+
+    #[test]
+    fn custom_piece_rotates_within_its_square() {
+        // A 3-block "L" in the top left corner of a 2x2 square
+        let cells = [Vec2I8::new(0, 0), Vec2I8::new(0, 1), Vec2I8::new(1, 1)];
+        let piece = PieceData::from_cells(PieceKind::L, &cells, Color::WHITE, KickTable::none());
+
+        let rotations: Vec<Vec<(u8, u8)>> = piece.states().iter().map(|s| s.iter_cells().collect()).collect();
+        assert_eq!(rotations, [
+            vec![(0, 0), (0, 1), (1, 1)],
+            vec![(0, 0), (0, 1), (1, 0)],
+            vec![(0, 0), (1, 0), (1, 1)],
+            vec![(0, 1), (1, 0), (1, 1)]
+        ]);
+    }
+
+    #[test]
+    fn mirrored_s_is_z() {
+        let pieces = PieceData::create_all_pieces();
+        let mirrored = pieces[PieceKind::S.index()].mirrored();
+        let z = &pieces[PieceKind::Z.index()];
+
+        assert_eq!(mirrored.kind(), PieceKind::Z);
+        for (a, b) in mirrored.states().iter().zip(z.states()) {
+            assert_eq!(a.matrix(), b.matrix());
+        }
+    }
+
+    #[test]
+    fn cell_counts_of_standard_and_custom_pieces() {
+        let i = &PieceData::create_all_pieces()[PieceKind::I.index()];
+        for state in i.states() {
+            assert_eq!(state.iter_cells().count(), 4);
+            assert_eq!(state.cell_count(), 4);
+        }
+
+        let cells = [Vec2I8::new(0, 0), Vec2I8::new(1, 0), Vec2I8::new(2, 0)];
+        let tromino = PieceData::from_cells(PieceKind::I, &cells, Color::WHITE, KickTable::none());
+        for state in tromino.states() {
+            assert_eq!(state.iter_cells().count(), 3);
+            assert_eq!(state.cell_count(), 3);
+        }
+
+        assert_eq!(tromino.state(0).iter_cells().collect::<Vec<_>>(), [(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn matrix_bits_round_trip() {
+        for piece in PieceData::create_all_pieces() {
+            for state in piece.states() {
+                let matrix = state.piece_matrix();
+                assert_eq!(PieceMatrix::from_bits(matrix.to_bits(), matrix.size()), Some(*matrix));
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_bits_are_rejected() {
+        assert_eq!(PieceMatrix::from_bits(0b1, 5), None);
+        assert_eq!(PieceMatrix::from_bits(0b1, 1), None);
+
+        // Bit 2 is the block at (0, 2), which does not fit into a 2x2 matrix
+        assert_eq!(PieceMatrix::from_bits(0b100, 2), None);
+        assert!(PieceMatrix::from_bits(0b100, 3).is_some());
+    }
+
+    #[test]
+    fn bool_matrix_import_keeps_blocks() {
+        let mut mat = [[false; 4]; 4];
+        mat[0][1] = true;
+        mat[1][1] = true;
+        mat[2][1] = true;
+        mat[2][2] = true;
+
+        let matrix = PieceMatrix::from(mat);
+        assert_eq!(matrix.matrix(), mat);
+        assert_eq!(matrix.size(), 3);
+
+        // The size is at least 2, even for a single block
+        let mut single = [[false; 4]; 4];
+        single[0][0] = true;
+        assert_eq!(PieceMatrix::from(single).size(), 2);
+    }
+}
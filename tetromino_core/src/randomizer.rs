@@ -0,0 +1,320 @@
+//! Defines how the game decides which pieces come next.
+
+use crate::pieces::{PieceData, PieceKind, PIECE_COUNT};
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+/// A source of pieces for a game.
+///
+/// Implementations pick the pieces from the full piece set they are given.
+pub trait Randomizer {
+    /// Picks the next piece out of all available pieces.
+    fn next_piece(&mut self, pieces: &[PieceData; PIECE_COUNT]) -> PieceData;
+
+    /// Creates a boxed copy of this randomizer in its current state,
+    /// which continues to produce the same pieces as the original.
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+
+/// A randomizer supplying the game with pieces from a shuffled bag.
+///
+/// It starts by filling a bag with all known pieces in random order, then draining that bag in order.
+/// Once the bag is empty, it is refilled right away.
+///
+/// The bags are shuffled with an rng of type `R`, which is [`StdRng`] unless specified otherwise.
+#[derive(Clone)]
+pub struct SevenBag<R: RngCore = StdRng> {
+    rng: R,
+    bag: [usize; PIECE_COUNT],
+    bag_left: usize
+}
+
+/// What a [`FixedSequence`] does once every piece of its sequence was drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SequenceEnd {
+    /// Keep drawing the last piece of the sequence.
+    RepeatLast,
+
+    /// Start over from the first piece of the sequence.
+    Cycle,
+
+    /// Panic when drawing another piece.
+    Panic
+}
+
+/// A randomizer supplying the game with pieces in a fixed, authored order.
+///
+/// Keep in mind that a game draws the upcoming pieces ahead of time, so the sequence runs out
+/// [`Game::preview_count()`] pieces before the last one of it becomes active.
+///
+/// [`Game::preview_count()`]: crate::game::Game::preview_count
+#[derive(Clone, Debug)]
+pub struct FixedSequence {
+    sequence: Vec<PieceKind>,
+    end: SequenceEnd,
+    position: usize
+}
+
+impl SevenBag {
+    /// Creates a new 7-bag with a freshly filled bag.
+    #[cfg(feature = "std")]
+    pub fn new() -> SevenBag {
+        SevenBag::with_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a new 7-bag with a freshly filled bag, whose sequence is determined by a seed.
+    pub fn with_seed(seed: u64) -> SevenBag {
+        SevenBag::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<R: RngCore> SevenBag<R> {
+    /// Creates a new 7-bag with a freshly filled bag, shuffling with the given rng.
+    pub fn with_rng(rng: R) -> SevenBag<R> {
+        let mut slf = SevenBag {
+            rng,
+            bag: [0; PIECE_COUNT],
+            bag_left: 0
+        };
+
+        slf.refill_bag();
+        slf
+    }
+
+    /// Gets the index of the next piece from the bag. The bag is automatically refilled when needed.
+    pub fn next_index(&mut self) -> usize {
+        self.bag_left -= 1;
+        let index = self.bag[self.bag_left];
+
+        if self.bag_left == 0 {
+            self.refill_bag();
+        }
+
+        index
+    }
+
+    /// Gets the piece that the next call to [`Randomizer::next_piece()`] will return, without drawing it.
+    pub fn peek_next<'a>(&self, pieces: &'a [PieceData; PIECE_COUNT]) -> &'a PieceData {
+        &pieces[self.bag[self.bag_left - 1]]
+    }
+
+    /// Gets the indices of the pieces that have not been drawn from the bag yet.
+    /// The last index is drawn next.
+    ///
+    /// Since the bag is refilled as soon as it is empty, this always holds at least one index.
+    pub fn remaining_in_bag(&self) -> &[usize] {
+        &self.bag[..self.bag_left]
+    }
+
+    /// Fills the bag with all pieces in random order.
+    fn refill_bag(&mut self) {
+        let mut new_bag = vec![0, 1, 2, 3, 4, 5, 6];
+        for i in 0..new_bag.len() {
+            self.bag[i] = new_bag.remove(self.rng.gen_range(0..new_bag.len()));
+        }
+
+        self.bag_left = PIECE_COUNT;
+    }
+}
+
+impl<R: RngCore + Clone + 'static> Randomizer for SevenBag<R> {
+    fn next_piece(&mut self, pieces: &[PieceData; PIECE_COUNT]) -> PieceData {
+        pieces[self.next_index()].clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+impl FixedSequence {
+    /// Creates a randomizer drawing the given pieces in order, then continuing as specified by `end`.
+    ///
+    /// Panics if the sequence is empty.
+    pub fn new(sequence: Vec<PieceKind>, end: SequenceEnd) -> FixedSequence {
+        assert!(!sequence.is_empty(), "a fixed sequence needs at least one piece");
+        FixedSequence {
+            sequence,
+            end,
+            position: 0
+        }
+    }
+
+    /// Gets the full sequence of pieces.
+    pub fn sequence(&self) -> &[PieceKind] {
+        &self.sequence
+    }
+
+    /// Gets what happens once the sequence was drawn.
+    pub fn end(&self) -> SequenceEnd {
+        self.end
+    }
+
+    /// Gets how many pieces have been drawn so far.
+    pub fn drawn(&self) -> usize {
+        self.position
+    }
+
+    /// Gets the kind of the next piece and advances through the sequence.
+    pub fn next_kind(&mut self) -> PieceKind {
+        let len = self.sequence.len();
+        let index = if self.position < len {
+            self.position
+        } else {
+            match self.end {
+                SequenceEnd::RepeatLast => len - 1,
+                SequenceEnd::Cycle => self.position % len,
+                SequenceEnd::Panic => panic!("the fixed sequence of {} pieces ran out", len)
+            }
+        };
+
+        self.position += 1;
+        self.sequence[index]
+    }
+}
+
+impl Randomizer for FixedSequence {
+    fn next_piece(&mut self, pieces: &[PieceData; PIECE_COUNT]) -> PieceData {
+        pieces[self.next_kind().index()].clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SevenBag {
+    fn default() -> Self {
+        SevenBag::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+
+    /// Gets the kinds of the active piece and the upcoming pieces, in the order they spawn.
+    fn spawn_order(game: &Game) -> Vec<PieceKind> {
+        let mut kinds = vec![game.active_piece().kind()];
+        kinds.extend(game.next_pieces().iter().map(PieceData::kind));
+        kinds
+    }
+
+    #[test]
+    fn seeded_games_spawn_same_pieces() {
+        let mut a = Game::with_seed(1234);
+        let mut b = Game::with_seed(1234);
+        a.set_preview_count(19);
+        b.set_preview_count(19);
+
+        let colors = |game: &Game| -> Vec<_> {
+            core::iter::once(&game.active_piece().piece_data).chain(game.next_pieces()).map(PieceData::color).collect()
+        };
+
+        assert_eq!(spawn_order(&a).len(), 20);
+        assert_eq!(spawn_order(&a), spawn_order(&b));
+        assert_eq!(colors(&a), colors(&b));
+
+        // The sequence goes on the same way after playing
+        a.hard_drop();
+        b.hard_drop();
+        assert_eq!(spawn_order(&a), spawn_order(&b));
+    }
+
+    #[test]
+    fn bag_draws_every_piece_once() {
+        let pieces = PieceData::create_all_pieces();
+        let mut bag = SevenBag::with_seed(99);
+
+        for _ in 0..3 {
+            assert_eq!(bag.remaining_in_bag().len(), PIECE_COUNT);
+
+            let mut seen = [false; PIECE_COUNT];
+            for left in (0..PIECE_COUNT).rev() {
+                let expected = bag.peek_next(&pieces).kind();
+                let piece = bag.next_piece(&pieces);
+                assert_eq!(piece.kind(), expected);
+                assert!(!seen[piece.kind().index()], "{} repeated within a bag", piece.kind());
+                seen[piece.kind().index()] = true;
+
+                if left > 0 {
+                    assert_eq!(bag.remaining_in_bag().len(), left);
+                }
+            }
+
+            assert!(seen.iter().all(|&s| s));
+        }
+    }
+
+    #[test]
+    fn scripted_randomizer_forces_opening() {
+        let opening = [PieceKind::S, PieceKind::Z, PieceKind::S, PieceKind::Z];
+        let mut game = Game::with_randomizer(Box::new(FixedSequence::new(opening.to_vec(), SequenceEnd::Cycle)));
+
+        for &kind in &opening {
+            assert_eq!(game.active_piece().kind(), kind);
+            game.hard_drop();
+        }
+    }
+
+    #[test]
+    fn small_rng_bags_are_reproducible() {
+        use rand::rngs::SmallRng;
+
+        let pieces = PieceData::create_all_pieces();
+        let mut a = SevenBag::with_rng(SmallRng::seed_from_u64(5));
+        let mut b = SevenBag::with_rng(SmallRng::seed_from_u64(5));
+
+        let draws = |bag: &mut SevenBag<SmallRng>| -> Vec<PieceKind> {
+            (0..21).map(|_| bag.next_piece(&pieces).kind()).collect()
+        };
+        assert_eq!(draws(&mut a), draws(&mut b));
+
+        // It also works as the randomizer of a game
+        let game_a = Game::with_randomizer(Box::new(SevenBag::with_rng(SmallRng::seed_from_u64(5))));
+        let game_b = Game::with_randomizer(Box::new(SevenBag::with_rng(SmallRng::seed_from_u64(5))));
+        assert_eq!(spawn_order(&game_a), spawn_order(&game_b));
+    }
+
+    #[test]
+    fn fixed_sequence_spawns_in_order() {
+        use PieceKind::*;
+
+        let sequence = vec![Z, S, T, O, L, J, I];
+        let mut game = Game::with_randomizer(Box::new(FixedSequence::new(sequence.clone(), SequenceEnd::Cycle)));
+        game.set_preview_count(6);
+        assert_eq!(spawn_order(&game), sequence);
+    }
+
+    #[test]
+    fn fixed_sequence_end_modes() {
+        use PieceKind::*;
+
+        let draw = |end: SequenceEnd| -> Vec<PieceKind> {
+            let mut randomizer = FixedSequence::new(vec![T, I, O], end);
+            (0..5).map(|_| randomizer.next_kind()).collect()
+        };
+
+        assert_eq!(draw(SequenceEnd::RepeatLast), [T, I, O, O, O]);
+        assert_eq!(draw(SequenceEnd::Cycle), [T, I, O, T, I]);
+    }
+
+    #[test]
+    #[should_panic(expected = "ran out")]
+    fn fixed_sequence_can_panic_at_the_end() {
+        let mut randomizer = FixedSequence::new(vec![PieceKind::T], SequenceEnd::Panic);
+        randomizer.next_kind();
+        randomizer.next_kind();
+    }
+}
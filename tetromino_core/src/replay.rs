@@ -0,0 +1,239 @@
+//! Defines recordings of games that can be played back.
+
+use crate::game::Game;
+use crate::input::{GameInput, HorizontalDir};
+use crate::score::ClearResult;
+
+use alloc::vec::Vec;
+
+/// A recording of a game started with [`Game::with_seed()`] and default settings.
+///
+/// Together with the seed, the inputs and ticks fully determine the game.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    /// The seed the game was created with.
+    pub seed: u64,
+
+    /// Every input and change of the held direction, in the order they happened.
+    pub inputs: Vec<ReplayInput>,
+
+    /// The time passed to every [`Game::tick()`] that was not skipped due to pausing.
+    pub ticks: Vec<f64>,
+}
+
+/// Something the player did during a recorded game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReplayAction {
+    /// An input applied with [`Game::handle_input()`].
+    Input(GameInput),
+
+    /// A direction held with [`Game::hold_direction()`].
+    HoldDirection(HorizontalDir),
+
+    /// A direction released with [`Game::release_direction()`].
+    ReleaseDirection(HorizontalDir),
+
+    /// The game was paused or resumed with [`Game::set_paused()`].
+    SetPaused(bool)
+}
+
+/// An action in a [`Replay`], along with when it happened.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayInput {
+    /// The [`Game::elapsed_seconds()`] at which the action happened.
+    pub time: f64,
+
+    /// The amount of recorded ticks before the action, which orders it even when ticks do not advance the time.
+    pub tick: usize,
+
+    /// What the player did.
+    pub action: ReplayAction,
+}
+
+/// Plays a game while recording it into a [`Replay`].
+pub struct ReplayRecorder {
+    game: Game,
+    replay: Replay,
+}
+
+impl Replay {
+    /// Plays the recording back, returning the game in its final state.
+    pub fn playback(&self) -> Game {
+        self.playback_until(f64::INFINITY)
+    }
+
+    /// Plays the recording back until the given play time in seconds, returning the game in that state.
+    pub fn playback_until(&self, seconds: f64) -> Game {
+        let mut game = Game::with_seed(self.seed);
+        let mut inputs = self.inputs.iter().peekable();
+        let mut ticks = self.ticks.iter().enumerate();
+
+        let mut tick_index = 0;
+        loop {
+            // Inputs happened before the tick that followed them
+            while let Some(input) = inputs.next_if(|input| input.tick <= tick_index && input.time <= seconds) {
+                input.action.apply(&mut game);
+            }
+
+            if game.elapsed_seconds() >= seconds {
+                return game;
+            }
+
+            match ticks.next() {
+                Some((index, &dt)) => {
+                    game.tick(dt);
+                    tick_index = index + 1;
+                }
+                None => return game
+            }
+        }
+    }
+}
+
+impl ReplayAction {
+    /// Applies the action to a game.
+    pub fn apply(self, game: &mut Game) {
+        match self {
+            ReplayAction::Input(input) => { game.handle_input(input); }
+            ReplayAction::HoldDirection(dir) => { game.hold_direction(dir); }
+            ReplayAction::ReleaseDirection(dir) => game.release_direction(dir),
+            ReplayAction::SetPaused(paused) => game.set_paused(paused)
+        }
+    }
+}
+
+impl ReplayRecorder {
+    /// Creates a new game with the given seed and starts recording it.
+    pub fn new(seed: u64) -> ReplayRecorder {
+        ReplayRecorder {
+            game: Game::with_seed(seed),
+            replay: Replay { seed, ..Replay::default() },
+        }
+    }
+
+    /// Gets the game being recorded.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Applies and records an input, as per [`Game::handle_input()`].
+    pub fn handle_input(&mut self, input: GameInput) -> bool {
+        // Inputs are ignored while paused, so there is nothing to play back
+        if !self.game.is_paused() {
+            self.record(ReplayAction::Input(input));
+        }
+
+        self.game.handle_input(input)
+    }
+
+    /// Holds and records a direction, as per [`Game::hold_direction()`].
+    pub fn hold_direction(&mut self, dir: HorizontalDir) -> bool {
+        // The held direction changes even while paused, so this is always recorded
+        self.record(ReplayAction::HoldDirection(dir));
+        self.game.hold_direction(dir)
+    }
+
+    /// Releases and records a direction, as per [`Game::release_direction()`].
+    pub fn release_direction(&mut self, dir: HorizontalDir) {
+        self.record(ReplayAction::ReleaseDirection(dir));
+        self.game.release_direction(dir);
+    }
+
+    /// Advances and records the game by `dt` seconds, as per [`Game::tick()`].
+    pub fn tick(&mut self, dt: f64) -> Option<ClearResult> {
+        // Paused ticks don't change the game, so there is nothing to play back
+        if !self.game.is_paused() {
+            self.replay.ticks.push(dt);
+        }
+
+        self.game.tick(dt)
+    }
+
+    /// Pauses or resumes the game, as per [`Game::set_paused()`].
+    pub fn set_paused(&mut self, paused: bool) {
+        // Playback has to pause as well, since holding a direction only moves the piece while not paused
+        self.record(ReplayAction::SetPaused(paused));
+        self.game.set_paused(paused);
+    }
+
+    /// Gets the recording so far.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Stops recording, returning the finished recording.
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+
+    /// Records an action at the current time.
+    fn record(&mut self, action: ReplayAction) {
+        self.replay.inputs.push(ReplayInput {
+            time: self.game.elapsed_seconds(),
+            tick: self.replay.ticks.len(),
+            action
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playback_matches_recorded_game() {
+        let mut recorder = ReplayRecorder::new(11);
+        recorder.handle_input(GameInput::RotateCW);
+        recorder.tick(0.1);
+        recorder.hold_direction(HorizontalDir::Left);
+        for _ in 0..20 {
+            recorder.tick(0.05);
+        }
+
+        recorder.release_direction(HorizontalDir::Left);
+        recorder.handle_input(GameInput::HardDrop);
+        recorder.handle_input(GameInput::Hold);
+        recorder.handle_input(GameInput::MoveRight);
+        recorder.tick(0.2);
+        recorder.handle_input(GameInput::HardDrop);
+
+        let original = recorder.game().playfield().clone();
+        let replay = recorder.finish();
+        assert!(replay.playback().playfield() == &original);
+    }
+
+    #[test]
+    fn inputs_between_zero_ticks_keep_their_order() {
+        let mut recorder = ReplayRecorder::new(5);
+        recorder.hold_direction(HorizontalDir::Right);
+        recorder.tick(0.0);
+        recorder.release_direction(HorizontalDir::Right);
+        recorder.tick(0.0);
+        recorder.hold_direction(HorizontalDir::Left);
+        recorder.tick(1.0);
+        recorder.handle_input(GameInput::HardDrop);
+
+        let original = recorder.game().playfield().clone();
+        let replay = recorder.finish();
+        let ticks: Vec<_> = replay.inputs.iter().map(|input| input.tick).collect();
+        assert_eq!(ticks, [0, 1, 2, 3]);
+        assert!(replay.playback().playfield() == &original);
+    }
+
+    #[test]
+    fn directions_held_while_paused_play_back_the_same() {
+        let mut recorder = ReplayRecorder::new(3);
+        recorder.set_paused(true);
+        recorder.hold_direction(HorizontalDir::Left);
+        recorder.release_direction(HorizontalDir::Left);
+        recorder.set_paused(false);
+        recorder.handle_input(GameInput::HardDrop);
+
+        let original = recorder.game().playfield().clone();
+        let replay = recorder.finish();
+        assert!(replay.playback().playfield() == &original);
+    }
+}
@@ -0,0 +1,222 @@
+//! Defines the scoring rules of the game.
+
+use alloc::vec::Vec;
+
+/// The amount of cleared lines needed to advance a level.
+pub const LINES_PER_LEVEL: usize = 10;
+
+/// The level a game starts at.
+pub const START_LEVEL: u32 = 1;
+
+/// The base points awarded per combo step, before the level multiplier.
+pub const COMBO_BONUS: u64 = 50;
+
+/// The points awarded per cell moved by soft-dropping.
+pub const SOFT_DROP_POINTS: u64 = 1;
+
+/// The points awarded per cell moved by hard-dropping.
+pub const HARD_DROP_POINTS: u64 = 2;
+
+/// Describes what kind of line clear happened when a piece was locked down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClearKind {
+    /// A regular clear, or no clear at all.
+    Normal,
+
+    /// A T-spin, where the T-piece was rotated into place with both front corners blocked.
+    TSpin,
+
+    /// A T-spin mini, where the T-piece was rotated into place with only one of its front corners blocked.
+    TSpinMini
+}
+
+/// The result of locking down a piece.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClearResult {
+    /// The amount of cleared lines.
+    pub lines: usize,
+
+    /// The cleared rows as they were before removal, counted from the top of the visible playfield,
+    /// in ascending order. These are the rows a renderer draws.
+    ///
+    /// Rows cleared within the non-visible part are not included, but still counted in [`ClearResult::lines`].
+    /// [`GameEvent::LinesCleared`] reports all cleared rows instead, as indices into the whole playfield
+    /// including its non-visible part.
+    ///
+    /// [`GameEvent::LinesCleared`]: crate::event::GameEvent::LinesCleared
+    pub cleared_rows: Vec<usize>,
+
+    /// The amount of cleared tiles that were garbage rather than part of a piece.
+    pub garbage_cleared: usize,
+
+    /// The kind of clear.
+    pub kind: ClearKind,
+
+    /// Whether the playfield is completely empty after clearing at least one line.
+    pub perfect_clear: bool
+}
+
+/// Tracks the points, level, and cleared lines of a game.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Score {
+    points: u64,
+    level: u32,
+    lines: usize,
+    combo: i32,
+    back_to_back: bool
+}
+
+/// Gets the base points awarded for clearing an amount of lines at once, before the level multiplier.
+pub fn line_clear_points(lines: usize, kind: ClearKind) -> u64 {
+    match kind {
+        ClearKind::Normal => match lines {
+            0 => 0,
+            1 => 100,
+            2 => 300,
+            3 => 500,
+            _ => 800
+        },
+        ClearKind::TSpin => match lines {
+            0 => 400,
+            1 => 800,
+            2 => 1200,
+            _ => 1600
+        },
+        ClearKind::TSpinMini => match lines {
+            0 => 100,
+            1 => 200,
+            _ => 400
+        }
+    }
+}
+
+/// Gets the base bonus points awarded for a perfect clear of an amount of lines, before the level multiplier.
+pub fn perfect_clear_points(lines: usize) -> u64 {
+    match lines {
+        0 => 0,
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        _ => 2000
+    }
+}
+
+impl Score {
+    /// Creates a new score with no points at the starting level.
+    pub fn new() -> Score {
+        Score {
+            points: 0,
+            level: START_LEVEL,
+            lines: 0,
+            combo: -1,
+            back_to_back: false
+        }
+    }
+
+    /// Gets the total amount of points.
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// Gets the current level.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Gets the total amount of cleared lines.
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Gets the current combo. This is `-1` if the last locked piece did not clear any lines,
+    /// and counts up from `0` for every consecutive piece that did.
+    pub fn combo(&self) -> i32 {
+        self.combo
+    }
+
+    /// Gets whether the last line clear was a tetris or a T-spin, so that another one would be back-to-back.
+    pub fn back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
+    /// Awards the points for soft-dropping a piece by some amount of cells.
+    pub fn add_soft_drop(&mut self, cells: usize) {
+        self.points += SOFT_DROP_POINTS * cells as u64;
+    }
+
+    /// Awards the points for hard-dropping a piece by some amount of cells.
+    pub fn add_hard_drop(&mut self, cells: usize) {
+        self.points += HARD_DROP_POINTS * cells as u64;
+    }
+
+    /// Awards the points for a line clear at the current level, including combo, back-to-back, and perfect clear bonuses.
+    /// A clear of no lines resets the combo.
+    /// The level is advanced afterwards if enough lines have been cleared.
+    pub fn add_clear(&mut self, result: &ClearResult) {
+        let mut points = line_clear_points(result.lines, result.kind);
+
+        if result.lines > 0 {
+            let difficult = result.lines >= 4 || result.kind != ClearKind::Normal;
+            if difficult && self.back_to_back {
+                points = points * 3 / 2;
+            }
+
+            self.back_to_back = difficult;
+            self.combo += 1;
+            points += COMBO_BONUS * self.combo as u64;
+
+            if result.perfect_clear {
+                points += perfect_clear_points(result.lines);
+            }
+        } else {
+            self.combo = -1;
+        }
+
+        self.points += points * self.level as u64;
+        self.lines += result.lines;
+        self.level = START_LEVEL + (self.lines / LINES_PER_LEVEL) as u32;
+    }
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Score::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates the result of a clear of no special kind.
+    fn clear(lines: usize) -> ClearResult {
+        ClearResult {
+            lines,
+            cleared_rows: Vec::new(),
+            garbage_cleared: 0,
+            kind: ClearKind::Normal,
+            perfect_clear: false
+        }
+    }
+
+    #[test]
+    fn tetris_at_level_5_scores_4000() {
+        let mut score = Score { level: 5, lines: 40, ..Score::new() };
+        score.add_clear(&clear(4));
+
+        assert_eq!(score.points(), 4000);
+    }
+
+    #[test]
+    fn level_advances_every_10_lines() {
+        let mut score = Score::new();
+        for _ in 0..9 {
+            score.add_clear(&clear(1));
+        }
+
+        assert_eq!(score.level(), 1);
+        score.add_clear(&clear(1));
+        assert_eq!(score.level(), 2);
+    }
+}
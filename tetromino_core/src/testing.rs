@@ -0,0 +1,98 @@
+//! Helpers for setting up exact game scenarios in tests, bypassing the randomizer.
+//!
+//! Only available with the `test-util` feature, and to the unit tests of this crate.
+//!
+//! ```
+//! use tetromino_core::{Color, Game, PieceKind};
+//!
+//! let mut game = Game::with_seed(0);
+//! let bottom = game.playfield().true_height() - 1;
+//!
+//! // Fill the bottom row except for the 4 columns the I-piece drops into
+//! let cells: Vec<_> = [0, 1, 2, 7, 8, 9].iter().map(|&x| (x, bottom)).collect();
+//! game.playfield_mut().set_cells(&cells, Color::WHITE);
+//!
+//! game.force_active_piece(PieceKind::I);
+//! let result = game.hard_drop().unwrap();
+//!
+//! assert_eq!(result.lines, 1);
+//! assert!(game.playfield().is_empty());
+//! ```
+
+use crate::{Color, Game, PieceKind, Playfield};
+
+impl Game {
+    /// Replaces the active piece with a freshly spawned piece of the given kind.
+    ///
+    /// Returns false if the piece overlaps at its spawn position. The game is not ended in that case.
+    pub fn force_active_piece(&mut self, kind: PieceKind) -> bool {
+        let piece = self.pieces[kind.index()].clone();
+        self.spawn_new_piece(piece)
+    }
+
+    /// Replaces the queue of upcoming pieces.
+    ///
+    /// Once a piece is taken from the front of the queue, the randomizer tops it up to [`Game::preview_count()`] again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kinds` is empty.
+    pub fn set_next_queue(&mut self, kinds: &[PieceKind]) {
+        assert!(!kinds.is_empty(), "next queue cannot be empty");
+        self.next_pieces = kinds.iter().map(|kind| self.pieces[kind.index()].clone()).collect();
+    }
+
+    /// Gets mutable access to the playfield.
+    pub fn playfield_mut(&mut self) -> &mut Playfield {
+        &mut self.playfield
+    }
+}
+
+impl Playfield {
+    /// Sets all the given tiles to the given color.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the positions is outside of the playfield.
+    pub fn set_cells(&mut self, cells: &[(usize, usize)], color: Color) {
+        for &(x, y) in cells {
+            let in_range = self.set_tile(x, y, color);
+            assert!(in_range, "cell is outside of the playfield");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forced_pieces_spawn_in_order() {
+        let mut game = Game::with_seed(0);
+        assert!(game.force_active_piece(PieceKind::T));
+        game.set_next_queue(&[PieceKind::S, PieceKind::Z]);
+
+        assert_eq!(game.active_piece().kind(), PieceKind::T);
+        game.hard_drop();
+        assert_eq!(game.active_piece().kind(), PieceKind::S);
+        game.hard_drop();
+        assert_eq!(game.active_piece().kind(), PieceKind::Z);
+    }
+
+    #[test]
+    fn forcing_into_the_stack_fails_without_ending_the_game() {
+        let mut game = Game::with_seed(0);
+        // Pieces spawn in the row just above the visible playfield
+        let spawn_row = game.playfield().height() - 1;
+        game.playfield_mut().set_cells(&[(3, spawn_row), (6, spawn_row)], Color::WHITE);
+
+        assert!(!game.force_active_piece(PieceKind::I));
+        assert!(!game.is_game_over());
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the playfield")]
+    fn set_cells_rejects_out_of_range() {
+        Playfield::new().set_cells(&[(10, 0)], Color::WHITE);
+    }
+}
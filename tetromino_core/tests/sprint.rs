@@ -0,0 +1,57 @@
+//! Plays a 40-line sprint with the greedy bot, covering the randomizer, placement, scoring, and line count together.
+
+use tetromino_core::{best_placement, EvalWeights, Game};
+
+/// The lines to clear to finish the sprint.
+const SPRINT_LINES: usize = 40;
+
+/// The most pieces the bot may use. Clearing 40 lines on a 10 wide playfield takes at least 100 pieces.
+const PIECE_BUDGET: u32 = 150;
+
+/// The seed all sprints are played with.
+const SEED: u64 = 40;
+
+/// The result of a sprint.
+#[derive(Debug, PartialEq)]
+struct SprintOutcome {
+    lines: usize,
+    pieces: u32,
+    score: u64,
+    game_over: bool
+}
+
+/// Plays a sprint with the greedy bot until it clears the lines, tops out, or runs out of pieces.
+fn play_sprint(seed: u64) -> SprintOutcome {
+    let mut game = Game::with_seed(seed);
+    let weights = EvalWeights::standard();
+
+    while game.lines_cleared() < SPRINT_LINES && game.pieces_placed() < PIECE_BUDGET && !game.is_game_over() {
+        let bot_move = best_placement(&game, &weights).expect("the bot found no placement");
+        if bot_move.hold {
+            game.hold_piece();
+        }
+
+        game.apply_placement(bot_move.placement).expect("the bot chose an invalid placement");
+    }
+
+    SprintOutcome {
+        lines: game.lines_cleared(),
+        pieces: game.pieces_placed(),
+        score: game.score(),
+        game_over: game.is_game_over()
+    }
+}
+
+#[test]
+fn greedy_bot_finishes_sprint_within_budget() {
+    let outcome = play_sprint(SEED);
+
+    assert!(!outcome.game_over, "the bot topped out: {:?}", outcome);
+    assert!(outcome.lines >= SPRINT_LINES, "the bot ran out of pieces: {:?}", outcome);
+    assert!(outcome.score > 0);
+}
+
+#[test]
+fn seeded_sprint_is_deterministic() {
+    assert_eq!(play_sprint(SEED), play_sprint(SEED));
+}
@@ -0,0 +1,28 @@
+//! Makes sure the core builds and plays without `std`.
+//!
+//! Cargo enables the features of a dependency that any crate being built asks for,
+//! so build this one on its own to check the `no_std` build: `cargo build -p tetromino_nostd_smoke`.
+
+#![no_std]
+
+use tetromino_core::{Game, GameInput};
+
+/// Hard-drops a few pieces in a seeded game, returning how many were placed.
+pub fn play_seeded_game(seed: u64) -> u32 {
+    let mut game = Game::with_seed(seed);
+    for _ in 0..10 {
+        game.handle_input(GameInput::HardDrop);
+    }
+
+    game.pieces_placed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_game_places_pieces() {
+        assert_eq!(play_seeded_game(0), 10);
+    }
+}
@@ -1,4 +1,4 @@
-use tetromino_core::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT, TRUE_PLAYFIELD_HEIGHT, Game, Color as TtColor};
+use tetromino_core::{Game, GameInput, HorizontalDir, PieceData, Color as TtColor};
 
 use glutin_window::GlutinWindow;
 use opengl_graphics::{GlGraphics, OpenGL};
@@ -6,10 +6,14 @@ use graphics::{Transformed, Context, color, rectangle};
 use graphics::math::Matrix2d;
 use graphics::types::{Color as GlColor, ColorComponent as GlColorComponent};
 use piston::event_loop::{Events, EventSettings, EventLoop};
-use piston::input::{UpdateEvent, Key, UpdateArgs, RenderArgs, PressEvent, RenderEvent, Button};
+use piston::input::{UpdateEvent, Key, UpdateArgs, RenderArgs, PressEvent, ReleaseEvent, RenderEvent, Button};
 use piston::window::WindowSettings;
 
-const EXTRA_LOCK_WAIT: f64 = 0.5;
+const HOLD_BOX_SIZE: f64 = 4.0;
+const NEXT_BOX_SIZE: f64 = 2.0;
+const RAINBOW_HUE_PER_SECOND: f64 = 90.0;
+const RAINBOW_HUE_PER_CELL: f64 = 12.0;
+const GHOST_BRIGHTNESS: f32 = 0.35;
 
 /// A piston-framework based implementation for the game.
 pub struct PistonGame {
@@ -17,10 +21,8 @@ pub struct PistonGame {
     window: GlutinWindow,
     gl: GlGraphics,
     render_scale: f64,
-    difficulty: u8,
-    auto_down_left: f64,
-    cleared_lines: usize,
-    is_game_over: bool,
+    rainbow: bool,
+    rainbow_time: f64,
 }
 
 /// Converts a tetromino-core color to a graphics color.
@@ -33,6 +35,22 @@ fn tetromino_to_graphics_color(c: TtColor) -> GlColor {
     ]
 }
 
+/// Computes the transform to draw a preview piece centered in a square box of the given size.
+///
+/// Each block is drawn at most `block_size` large, but shrunk if the piece would not fit otherwise.
+fn preview_piece_trs(box_trs: Matrix2d, box_size: f64, block_size: f64, piece: &PieceData) -> Matrix2d {
+    let (min, max) = piece.state(0).bounds();
+    let (min, max) = (min.as_f64(), max.as_f64());
+    let width = max.x - min.x + 1.0;
+    let height = max.y - min.y + 1.0;
+    let scale = block_size.min(box_size / width).min(box_size / height);
+
+    box_trs
+        .trans((box_size - width * scale) * 0.5, (box_size - height * scale) * 0.5)
+        .scale(scale, scale)
+        .trans(-min.x, -min.y)
+}
+
 impl PistonGame {
     /// Creates a new instance of the game with a specified render scale.
     pub fn new(render_scale: f64) -> PistonGame {
@@ -51,13 +69,17 @@ impl PistonGame {
             window,
             gl,
             render_scale,
-            difficulty: 1,
-            auto_down_left: 2.0,
-            cleared_lines: 0,
-            is_game_over: false,
+            rainbow: false,
+            rainbow_time: 0.0,
         }
     }
 
+    /// Sets whether locked blocks are rendered with a hue cycling over time instead of their real color.
+    /// The active piece always keeps its real color.
+    pub fn set_rainbow(&mut self, rainbow: bool) {
+        self.rainbow = rainbow;
+    }
+
     /// Runs the game. This is the only method you will need to call.
     pub fn run(&mut self) {
         let mut event_settings = EventSettings::new();
@@ -69,7 +91,7 @@ impl PistonGame {
                 self.render(&ra);
             }
 
-            if !self.is_game_over {
+            if !self.game.is_game_over() {
                 if let Some(ua) = e.update_args() {
                     self.update(&ua);
                 }
@@ -77,6 +99,13 @@ impl PistonGame {
                 if let Some(Button::Keyboard(key)) = e.press_args() {
                     self.on_key_press(&key);
                 }
+
+                if let Some(Button::Keyboard(key)) = e.release_args() {
+                    self.on_key_release(&key);
+                }
+            } else if let Some(Button::Keyboard(Key::Return)) = e.press_args() {
+                // Start over once the game is lost
+                self.game.reset();
             }
         }
     }
@@ -88,9 +117,13 @@ impl PistonGame {
             render_args.window_size[1] / (2.0 * self.render_scale)
         );
 
-        let top_left = (center.0 - (PLAYFIELD_WIDTH as f64) * 0.5, center.1 - (PLAYFIELD_HEIGHT as f64) * 0.5);
+        let field_width = self.game.playfield().width() as f64;
+        let field_height = self.game.playfield().height() as f64;
+        let top_left = (center.0 - field_width * 0.5, center.1 - field_height * 0.5);
+        let hidden_rows = self.game.playfield().visible_rows().start as f64;
         let render_scale = self.render_scale;
         let active_piece = self.game.active_piece();
+        let active_position = active_piece.position.as_f64();
 
         self.gl.draw_begin(render_args.viewport());
 
@@ -104,48 +137,62 @@ impl PistonGame {
                 .trans(top_left.0, top_left.1);
 
             // Render a background
-            rectangle(color::grey(0.15), rectangle::rectangle_by_corners(0.0, 0.0, 10.0, 20.0), field_trs, &mut self.gl);
+            rectangle(color::grey(0.15), rectangle::rectangle_by_corners(0.0, 0.0, field_width, field_height), field_trs, &mut self.gl);
 
-            // Render the playing field
+            // Render the visible part of the playing field
             let playfield = self.game.playfield();
-            let full_field_trs = field_trs.trans(0.0, -(PLAYFIELD_HEIGHT as f64));
-            for x in 0..PLAYFIELD_WIDTH {
-                for y in 0..TRUE_PLAYFIELD_HEIGHT {
-                    let tile = playfield.get_tile(x, y);
-                    if !tile.is_black() {
-                        let color = tetromino_to_graphics_color(tile);
-                        let block_trs = full_field_trs.trans(x as f64, y as f64);
-                        rectangle(color, square, block_trs, &mut self.gl);
-                    }
+            let palette = self.game.palette();
+            for (x, y, tile) in playfield.visible_tiles() {
+                if !tile.is_black() {
+                    let tile = if self.rainbow {
+                        let hue = self.rainbow_time * RAINBOW_HUE_PER_SECOND + (x + y) as f64 * RAINBOW_HUE_PER_CELL;
+                        TtColor::from_hsv(hue as f32, 0.75, 0.95)
+                    } else {
+                        playfield.tile_color(x, y + playfield.visible_rows().start, palette)
+                    };
+
+                    let color = tetromino_to_graphics_color(tile);
+                    let block_trs = field_trs.trans(x as f64, y as f64);
+                    rectangle(color, square, block_trs, &mut self.gl);
                 }
             }
 
+            // Render the ghost piece where the active piece would land
+            let ghost_piece = self.game.ghost_piece();
+            let ghost_position = ghost_piece.position.as_f64();
+            draw_piece(
+                &mut self.gl,
+                field_trs.trans(ghost_position.x, ghost_position.y - hidden_rows),
+                &ghost_piece.matrix(),
+                self.game.piece_color(&ghost_piece.piece_data).scaled(GHOST_BRIGHTNESS)
+            );
+
             // Render the active piece
             draw_piece(
                 &mut self.gl,
-                field_trs.trans(active_piece.position.x as f64, active_piece.position.y as f64 - 20.0),
+                field_trs.trans(active_position.x, active_position.y - hidden_rows),
                 &active_piece.matrix(),
-                active_piece.piece_data.color()
+                self.game.piece_color(&active_piece.piece_data)
             );
 
             // Render the held piece, if any
             if let Some(held_piece) = self.game.held_piece() {
                 draw_piece(
                     &mut self.gl,
-                    field_trs.trans(-5.0, 0.0),
+                    preview_piece_trs(field_trs.trans(-5.0, 0.0), HOLD_BOX_SIZE, 1.0, held_piece),
                     &held_piece.default_matrix(),
-                    held_piece.color()
+                    self.game.piece_color(held_piece)
                 );
             }
 
             // Also draw the list of upcoming pieces
-            let next_trs = field_trs.trans(11.0, 0.0).scale(0.5, 0.5);
+            let next_trs = field_trs.trans(11.0, 0.0);
             for (i, np) in self.game.next_pieces().iter().enumerate() {
                 draw_piece(
                     &mut self.gl,
-                    next_trs.trans(0.0, (i as f64) * 4.5),
+                    preview_piece_trs(next_trs.trans(0.0, (i as f64) * 2.25), NEXT_BOX_SIZE, 0.5, np),
                     &np.default_matrix(),
-                    np.color()
+                    self.game.piece_color(np)
                 );
             }
 
@@ -153,9 +200,9 @@ impl PistonGame {
             fn draw_piece(gl: &mut opengl_graphics::GlGraphics, piece_trs: Matrix2d, piece_mtrx: &[[bool; 4]; 4], color: TtColor) {
                 let square = rectangle::square(0.0, 0.0, 1.0);
                 let color = tetromino_to_graphics_color(color);
-                for x in 0..4 {
-                    for y in 0..4 {
-                        if piece_mtrx[x][y] {
+                for (x, column) in piece_mtrx.iter().enumerate() {
+                    for (y, &filled) in column.iter().enumerate() {
+                        if filled {
                             let block_trs = piece_trs.trans(x as f64, y as f64);
                             rectangle(color, square, block_trs, gl);
                         }
@@ -169,81 +216,65 @@ impl PistonGame {
 
     /// Updates the game based on the update step.
     fn update(&mut self, update_args: &UpdateArgs) {
-        self.auto_down_left -= update_args.dt;
-        
-        if self.auto_down_left < 0.0 {
-            if !self.game.move_down() {
-                if self.auto_down_left < -EXTRA_LOCK_WAIT {
-                    self.update_prepare_next_piece();
-                }
-            } else {
-                self.auto_down_left += self.get_auto_down_time();
-            }
-        }
-    }
-
-    /// Prepares dropping the next piece within the update step.
-    fn update_prepare_next_piece(&mut self) {
-        if let Some(cl) = self.game.finish_piece_turn() {
-            self.cleared_lines += cl;
-            self.auto_down_left = self.get_auto_down_time();
+        self.rainbow_time += update_args.dt;
 
-            let new_diff = 1 + self.cleared_lines / 2;
-            self.difficulty = if new_diff <= 9 { new_diff as u8 } else { 9 };
-        } else {
-            self.is_game_over = true;
-        }
+        // The core handles gravity and locks the piece once it has been resting long enough
+        self.game.tick(update_args.dt);
     }
 
     /// Called when a key is pressed. Used for handling input.
     fn on_key_press(&mut self, key: &Key) {
-        match key {
-            // Move left
-            Key::A | Key::Left => {
-                self.game.move_left();
-            }
-
-            // Move right
-            Key::D | Key::Right => {
-                self.game.move_right();
-            }
+        let input = match key {
+            // Move left or right, repeating while held
+            Key::A | Key::Left => return self.hold_direction(HorizontalDir::Left),
+            Key::D | Key::Right => return self.hold_direction(HorizontalDir::Right),
 
             // Move down faster
-            Key::S | Key::Down => {
-                if self.game.move_down() {
-                    self.auto_down_left = self.get_auto_down_time();
-                }
-            }
+            Key::S | Key::Down => GameInput::SoftDrop,
 
             // Rotate-left
-            Key::Q => {
-                self.game.rotate_left();
-            }
+            Key::Q => GameInput::RotateCCW,
 
             // Rotate-right
-            Key::W | Key::Up => {
-                self.game.rotate_right();
-            }
+            Key::W | Key::Up => GameInput::RotateCW,
+
+            // Rotate-180
+            Key::R => GameInput::Rotate180,
 
             // Quick-drop
-            Key::Space => {
-                self.game.quick_drop();
-                self.update_prepare_next_piece();
-            }
+            Key::Space => GameInput::HardDrop,
 
             // Hold/Swap
-            Key::E => {
-                self.game.hold_piece();
-                self.auto_down_left = self.get_auto_down_time();
+            Key::E => GameInput::Hold,
+
+            // Pause/Resume
+            Key::P => {
+                let paused = self.game.is_paused();
+                self.game.set_paused(!paused);
+                return;
             }
 
             // Don't care about the other keys
-            _ => ()
+            _ => return
         };
+
+        self.game.handle_input(input);
     }
 
-    /// Gets the delay between automatic moves down.
-    fn get_auto_down_time(&self) -> f64 {
-        2.0 / (self.difficulty as f64 + 0.5)
+    /// Called when a key is released. Used for stopping held directions.
+    fn on_key_release(&mut self, key: &Key) {
+        match key {
+            Key::A | Key::Left => self.game.release_direction(HorizontalDir::Left),
+            Key::D | Key::Right => self.game.release_direction(HorizontalDir::Right),
+            _ => ()
+        }
+    }
+
+    /// Starts holding a direction, unless it is already held.
+    /// Repeated key presses sent by the OS would otherwise restart the auto-shift delay.
+    fn hold_direction(&mut self, dir: HorizontalDir) {
+        if self.game.held_direction() != Some(dir) {
+            self.game.hold_direction(dir);
+        }
     }
 }
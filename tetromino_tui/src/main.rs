@@ -0,0 +1,170 @@
+//! Plays the game in a terminal, for when OpenGL is not available.
+
+use std::io::{stdout, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::style::{Color as TermColor, Print, ResetColor, SetForegroundColor};
+use crossterm::{cursor, queue, terminal};
+use tetromino_core::{Color as TtColor, Game, GameInput, PieceData};
+
+/// How long to wait for input before updating the game again.
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+/// The amount of upcoming pieces listed in the side panel.
+const NEXT_PREVIEW_COUNT: usize = 5;
+
+/// The column the side panel starts at.
+const PANEL_COLUMN: u16 = 26;
+
+/// How bright the ghost piece is drawn compared to the active piece.
+const GHOST_BRIGHTNESS: f32 = 0.35;
+
+/// What the main loop should do after handling a key.
+enum Action {
+    Continue,
+    Quit
+}
+
+fn main() -> crossterm::Result<()> {
+    let mut game = Game::new();
+    let mut out = stdout();
+
+    terminal::enable_raw_mode()?;
+    queue!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = run(&mut game, &mut out);
+
+    // Always restore the terminal, even if drawing failed
+    queue!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    out.flush()?;
+    terminal::disable_raw_mode()?;
+    result?;
+
+    println!("Game over! Score: {}, Level: {}, Lines: {}", game.score(), game.level(), game.lines_cleared());
+    Ok(())
+}
+
+/// Runs the game until it is over or the player quits.
+fn run(game: &mut Game, out: &mut Stdout) -> crossterm::Result<()> {
+    let mut last_frame = Instant::now();
+
+    while !game.is_game_over() {
+        if event::poll(FRAME_TIME)? {
+            if let Event::Key(key) = event::read()? {
+                if let Action::Quit = on_key_press(game, key) {
+                    return Ok(());
+                }
+            }
+        }
+
+        // The core handles gravity and locks the piece once it has been resting long enough
+        let now = Instant::now();
+        game.tick((now - last_frame).as_secs_f64());
+        last_frame = now;
+
+        draw(game, out)?;
+    }
+
+    Ok(())
+}
+
+/// Applies a key press to the game.
+fn on_key_press(game: &mut Game, key: KeyEvent) -> Action {
+    let input = match key.code {
+        KeyCode::Left | KeyCode::Char('a') => GameInput::MoveLeft,
+        KeyCode::Right | KeyCode::Char('d') => GameInput::MoveRight,
+        KeyCode::Down | KeyCode::Char('s') => GameInput::SoftDrop,
+        KeyCode::Up | KeyCode::Char('w') => GameInput::RotateCW,
+        KeyCode::Char('q') => GameInput::RotateCCW,
+        KeyCode::Char('r') => GameInput::Rotate180,
+        KeyCode::Char(' ') => GameInput::HardDrop,
+        KeyCode::Char('e') => GameInput::Hold,
+
+        // Pause/Resume
+        KeyCode::Char('p') => {
+            let paused = game.is_paused();
+            game.set_paused(!paused);
+            return Action::Continue;
+        }
+
+        KeyCode::Esc => return Action::Quit,
+
+        // Don't care about the other keys
+        _ => return Action::Continue
+    };
+
+    game.handle_input(input);
+    Action::Continue
+}
+
+/// Draws the playfield and the side panel.
+fn draw(game: &Game, out: &mut Stdout) -> crossterm::Result<()> {
+    // Put the pieces on a copy of the playfield, so they can be drawn like any other tile
+    let mut board = game.playfield().clone();
+    let ghost = game.ghost_piece();
+    let ghost_color = game.piece_color(&ghost.piece_data).scaled(GHOST_BRIGHTNESS);
+    for cell in ghost.occupied_cells() {
+        if let Some(tile) = board.get_tile_mut(cell.x as usize, cell.y as usize) {
+            *tile = ghost_color;
+        }
+    }
+
+    board.copy_in_piece(game.active_piece());
+
+    queue!(out, terminal::Clear(terminal::ClearType::All))?;
+    let hidden_rows = board.visible_rows().start;
+    for (x, y, _) in board.visible_tiles() {
+        let tile = board.tile_color(x, y + hidden_rows, game.palette());
+        queue!(out, cursor::MoveTo(2 + x as u16 * 2, 1 + y as u16))?;
+        if tile.is_black() {
+            queue!(out, SetForegroundColor(TermColor::DarkGrey), Print(" ."))?;
+        } else {
+            queue!(out, SetForegroundColor(to_term_color(tile)), Print("██"))?;
+        }
+    }
+
+    // Tiles above the visible rows are not drawn, so only the floor is framed
+    let bottom = 1 + board.height() as u16;
+    queue!(
+        out,
+        ResetColor,
+        cursor::MoveTo(1, bottom),
+        Print("└".to_string() + &"─".repeat(board.width() * 2) + "┘")
+    )?;
+
+    let mut line = 1;
+    let mut panel = |out: &mut Stdout, text: String| -> crossterm::Result<()> {
+        queue!(out, cursor::MoveTo(PANEL_COLUMN, line), Print(text))?;
+        line += 1;
+        Ok(())
+    };
+
+    panel(out, format!("Score: {}", game.score()))?;
+    panel(out, format!("Level: {}", game.level()))?;
+    panel(out, format!("Lines: {}", game.lines_cleared()))?;
+    panel(out, String::new())?;
+    panel(out, format!("Hold:  {}", game.held_piece().map_or('-', piece_letter)))?;
+    panel(out, format!(
+        "Next:  {}",
+        game.next_piece_kinds(NEXT_PREVIEW_COUNT).iter().map(|kind| kind.letter()).collect::<String>()
+    ))?;
+
+    if game.is_paused() {
+        panel(out, String::new())?;
+        panel(out, "Paused".to_string())?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Gets the letter a piece is named after.
+fn piece_letter(piece: &PieceData) -> char {
+    piece.kind().letter()
+}
+
+/// Converts a core color into a terminal color.
+fn to_term_color(color: TtColor) -> TermColor {
+    TermColor::Rgb { r: color.r, g: color.g, b: color.b }
+}
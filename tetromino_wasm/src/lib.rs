@@ -0,0 +1,145 @@
+//! Exposes the game to JavaScript through `wasm-bindgen`.
+//!
+//! Build it with `wasm-pack build --target web tetromino_wasm` and serve the `tetromino_wasm` folder
+//! to try the example in `www/index.html`.
+
+use tetromino_core::{Game, GameInput, PieceData, PieceKind};
+use wasm_bindgen::prelude::*;
+
+/// The amount of bytes per tile returned by [`WasmGame::render_cells()`].
+const BYTES_PER_TILE: usize = 4;
+
+/// A player input, passed to [`WasmGame::input()`].
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+    Hold
+}
+
+/// A game that is driven and drawn from JavaScript.
+#[wasm_bindgen]
+pub struct WasmGame {
+    game: Game
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    /// Creates a new game whose piece sequence is determined by a seed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> WasmGame {
+        WasmGame {
+            game: Game::with_seed(seed as u64)
+        }
+    }
+
+    /// Advances the game by `dt` seconds.
+    pub fn tick(&mut self, dt: f64) {
+        self.game.tick(dt);
+    }
+
+    /// Applies a player input. Returns whether it had any effect.
+    pub fn input(&mut self, input: Input) -> bool {
+        self.game.handle_input(input.into())
+    }
+
+    /// Gets the width of the playfield in tiles.
+    pub fn width(&self) -> usize {
+        self.game.playfield().width()
+    }
+
+    /// Gets the visible height of the playfield in tiles.
+    pub fn height(&self) -> usize {
+        self.game.playfield().height()
+    }
+
+    /// Packs the visible playfield, including the active piece, into a flat RGBA buffer.
+    ///
+    /// Tiles are stored row by row from the top left, with 4 bytes each. Empty tiles are fully transparent,
+    /// so the buffer can be put into an `ImageData` as-is.
+    pub fn render_cells(&self) -> Vec<u8> {
+        let mut playfield = self.game.playfield().clone();
+        playfield.copy_in_piece(self.game.active_piece());
+
+        let mut cells = Vec::with_capacity(playfield.width() * playfield.height() * BYTES_PER_TILE);
+        let palette = self.game.palette();
+        let hidden_rows = playfield.visible_rows().start;
+        for (x, y, _) in playfield.visible_tiles() {
+            let tile = playfield.tile_color(x, y + hidden_rows, palette);
+            let alpha = if tile.is_black() { 0x00 } else { 0xff };
+            cells.extend_from_slice(&[tile.r, tile.g, tile.b, alpha]);
+        }
+
+        cells
+    }
+
+    /// Gets the total score. It is exact up to 2^53 points.
+    pub fn score(&self) -> f64 {
+        self.game.score() as f64
+    }
+
+    /// Gets the current level.
+    pub fn level(&self) -> u32 {
+        self.game.level()
+    }
+
+    /// Gets the total amount of cleared lines.
+    pub fn lines(&self) -> usize {
+        self.game.lines_cleared()
+    }
+
+    /// Gets the letters of the upcoming pieces, in order.
+    pub fn next_pieces(&self) -> String {
+        self.game.next_pieces().iter().map(piece_letter).collect()
+    }
+
+    /// Gets the letter of the held piece, if any.
+    pub fn held_piece(&self) -> Option<char> {
+        self.game.held_piece().map(piece_letter)
+    }
+
+    /// Gets the color of a piece by its letter in the current palette as a CSS hex string,
+    /// or [`None`] if there is no such piece.
+    pub fn piece_color(&self, letter: char) -> Option<String> {
+        PieceKind::ALL
+            .iter()
+            .find(|kind| kind.letter() == letter)
+            .map(|&kind| self.game.palette().color(kind).to_hex())
+    }
+
+    /// Determines whether the game is over.
+    pub fn is_game_over(&self) -> bool {
+        self.game.is_game_over()
+    }
+
+    /// Restarts the game with the same seed.
+    pub fn reset(&mut self) {
+        self.game.reset();
+    }
+}
+
+impl From<Input> for GameInput {
+    fn from(input: Input) -> Self {
+        match input {
+            Input::MoveLeft => GameInput::MoveLeft,
+            Input::MoveRight => GameInput::MoveRight,
+            Input::SoftDrop => GameInput::SoftDrop,
+            Input::HardDrop => GameInput::HardDrop,
+            Input::RotateCW => GameInput::RotateCW,
+            Input::RotateCCW => GameInput::RotateCCW,
+            Input::Rotate180 => GameInput::Rotate180,
+            Input::Hold => GameInput::Hold
+        }
+    }
+}
+
+/// Gets the letter a piece is named after.
+fn piece_letter(piece: &PieceData) -> char {
+    piece.kind().letter()
+}